@@ -0,0 +1,226 @@
+//! Keyboard cursor over cycles of waves. Click on plot places it, arrows move it,
+//! `0`, `1`, `X`, `Z` and Space set one bit wave and move to next cycle. Digit or Enter
+//! starts inline edit of register, Enter in edit saves value and moves to next cycle.
+
+use egui::{
+    text::{CCursor, CCursorRange},
+    InputState, Key, Modifiers, Ui,
+};
+
+use super::waves::{Wave, WaveId};
+
+const DIGITS: [(Key, char); 10] = [
+    (Key::Num0, '0'),
+    (Key::Num1, '1'),
+    (Key::Num2, '2'),
+    (Key::Num3, '3'),
+    (Key::Num4, '4'),
+    (Key::Num5, '5'),
+    (Key::Num6, '6'),
+    (Key::Num7, '7'),
+    (Key::Num8, '8'),
+    (Key::Num9, '9'),
+];
+
+#[derive(Default)]
+pub struct EditCursor {
+    /// Wave and cycle
+    pos: Option<(WaveId, usize)>,
+    /// Text of inline edit
+    input: Option<String>,
+    error: Option<String>,
+    /// Cursor moved by keys, view follows it
+    moved: bool,
+}
+
+impl EditCursor {
+    fn index(&self, waves: &[Wave]) -> Option<(usize, usize)> {
+        let (id, cycle) = self.pos?;
+        let index = waves.iter().position(|w| w.id() == id)?;
+        Some((index, cycle))
+    }
+
+    fn set(&mut self, waves: &[Wave], index: usize, cycle: usize) {
+        let last = waves[index].len().saturating_sub(1);
+        self.pos = Some((waves[index].id(), cycle.min(last)));
+        self.moved = true;
+    }
+
+    /// Click without modifiers on plot places cursor, any other click in plot hides it
+    pub fn click(&mut self, waves: &[Wave], input: &InputState) {
+        let pointer = &input.pointer;
+        let Some(pos) = pointer.interact_pos().filter(|_| pointer.primary_clicked()) else {
+            return;
+        };
+        let Some(index) = waves.iter().position(|w| w.plot_contains(pos)) else {
+            return;
+        };
+        self.input = None;
+        self.error = None;
+        match waves[index].cycle_at(pos) {
+            Some(cycle) if input.modifiers.is_none() => self.set(waves, index, cycle),
+            _ => self.pos = None,
+        }
+    }
+
+    /// Keys of cursor are consumed, so waves don't see them. `order` is indexes of
+    /// shown waves from top to bottom
+    pub fn keys(&mut self, input: &mut InputState, waves: &mut [Wave], order: &[usize]) {
+        if self.input.is_some() {
+            return;
+        }
+        let Some((index, cycle)) = self.index(waves) else {
+            self.pos = None;
+            return;
+        };
+        let mut key = |k| input.consume_key(Modifiers::NONE, k);
+        if key(Key::Escape) {
+            self.pos = None;
+            return;
+        }
+        let row = order.iter().position(|i| *i == index);
+        let target = if key(Key::ArrowLeft) {
+            Some((index, cycle.saturating_sub(1)))
+        } else if key(Key::ArrowRight) {
+            Some((index, cycle + 1))
+        } else if key(Key::ArrowUp) {
+            let up = row.and_then(|r| r.checked_sub(1)).map(|r| order[r]);
+            up.map(|i| (i, cycle))
+        } else if key(Key::ArrowDown) {
+            let down = row.and_then(|r| order.get(r + 1));
+            down.map(|i| (*i, cycle))
+        } else {
+            None
+        };
+        if let Some((index, cycle)) = target {
+            self.set(waves, index, cycle);
+            return;
+        }
+        let wave = &mut waves[index];
+        if !wave.editable() {
+            return;
+        }
+        if wave.is_wire() {
+            let levels = [
+                (Key::Num0, "1'b0"),
+                (Key::Num1, "1'b1"),
+                (Key::X, "1'bx"),
+                (Key::Z, "1'bz"),
+            ];
+            let level = levels.into_iter().find(|(k, _)| key(*k));
+            if let Some(v) = level.and_then(|(_, v)| wave.parse_sample(cycle, v).ok()) {
+                wave.set_sample(cycle, v);
+                self.set(waves, index, cycle + 1);
+            } else if key(Key::Space) {
+                wave.toggle_sample(cycle);
+                self.set(waves, index, cycle + 1);
+            }
+        } else if key(Key::Enter) {
+            self.input = Some(wave.sample_text(cycle));
+        } else if let Some((_, c)) = DIGITS.iter().find(|(k, _)| key(*k)) {
+            self.input = Some(c.to_string());
+        }
+    }
+
+    /// Frame of cursor and inline edit over plot. Returns cycle of cursor out of view
+    pub fn draw(&mut self, ui: &mut Ui, waves: &mut [Wave]) -> Option<usize> {
+        let (index, cycle) = self.index(waves)?;
+        let Some(rect) = waves[index].cycle_rect(cycle) else {
+            return std::mem::take(&mut self.moved).then_some(cycle);
+        };
+        if std::mem::take(&mut self.moved) {
+            ui.scroll_to_rect(rect, None);
+        }
+        let stroke = ui.visuals().selection.stroke;
+        ui.painter().rect_stroke(rect.expand(1.0), 0.0, stroke);
+        let Some(text) = &mut self.input else {
+            return None;
+        };
+        let mut done = None;
+        egui::Area::new("inline_edit")
+            .fixed_pos(rect.left_top())
+            .order(egui::Order::Foreground)
+            .show(ui.ctx(), |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    let len = text.chars().count();
+                    let edit = egui::TextEdit::singleline(text).desired_width(120.0);
+                    let mut output = edit.show(ui);
+                    let r = &output.response;
+                    if r.lost_focus() {
+                        done = Some(ui.input(|i| i.key_pressed(Key::Enter)));
+                    } else if !r.has_focus() {
+                        // Typing continues after first digit
+                        r.request_focus();
+                        let end = CCursorRange::one(CCursor::new(len));
+                        output.state.set_ccursor_range(Some(end));
+                        output.state.store(ui.ctx(), r.id);
+                    }
+                    if let Some(e) = &self.error {
+                        ui.colored_label(egui::Color32::RED, e);
+                    }
+                });
+            });
+        match done {
+            Some(true) => match waves[index].parse_sample(cycle, text) {
+                Ok(v) => {
+                    waves[index].set_sample(cycle, v);
+                    self.input = None;
+                    self.error = None;
+                    self.set(waves, index, cycle + 1);
+                }
+                Err(e) => self.error = Some(e),
+            },
+            Some(false) => {
+                self.input = None;
+                self.error = None;
+            }
+            None => {}
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use egui::{Event, InputState, Key, Modifiers, Vec2};
+
+    use super::EditCursor;
+    use crate::app::waves::{Wave, WaveType};
+
+    fn press(cursor: &mut EditCursor, waves: &mut [Wave], key: Key) {
+        let mut input = InputState::default();
+        input.events.push(Event::Key {
+            key,
+            pressed: true,
+            repeat: false,
+            modifiers: Modifiers::NONE,
+        });
+        cursor.keys(&mut input, waves, &[1, 0]);
+        assert!(input.events.is_empty());
+    }
+
+    #[test]
+    fn test_keys() {
+        let mut waves = vec![Wave::new("a", 4, Vec2::ZERO), Wave::new("b", 4, Vec2::ZERO)];
+        waves[0].set_type(WaveType::Reg(8));
+        let mut cursor = EditCursor::default();
+        cursor.set(&waves, 1, 2);
+
+        press(&mut cursor, &mut waves, Key::Num1);
+        press(&mut cursor, &mut waves, Key::Space);
+        assert_eq!(cursor.pos, Some((waves[1].id(), 3)));
+        assert_eq!(waves[1].sample_text(2), "1'b1");
+        assert_eq!(waves[1].sample_text(3), "1'b1");
+
+        press(&mut cursor, &mut waves, Key::ArrowDown);
+        assert_eq!(cursor.index(&waves), Some((0, 3)));
+        press(&mut cursor, &mut waves, Key::ArrowRight);
+        assert_eq!(cursor.index(&waves), Some((0, 3)));
+        press(&mut cursor, &mut waves, Key::Num7);
+        assert_eq!(cursor.input.as_deref(), Some("7"));
+
+        cursor.input = None;
+        press(&mut cursor, &mut waves, Key::Escape);
+        assert_eq!(cursor.pos, None);
+    }
+}
@@ -0,0 +1,462 @@
+//! Named nestable groups of waves. Waves keep id of their group, groups keep id of parent.
+//! Group is shown at place of its first wave, so waves of one group are drawn together.
+
+use egui::Ui;
+use serde::{Deserialize, Serialize};
+
+use crate::hseparator;
+
+use super::{
+    history::Command,
+    waves::{Wave, WaveId, WaveMove},
+};
+
+/// Groups take ids from same space as waves
+pub type GroupId = WaveId;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct WaveGroup {
+    pub id: GroupId,
+    pub name: String,
+    pub parent: Option<GroupId>,
+    /// Only header row is shown
+    pub collapsed: bool,
+}
+
+impl WaveGroup {
+    pub fn new<T: Into<String>>(name: T, parent: Option<GroupId>) -> Self {
+        Self {
+            id: GroupId::default(),
+            name: name.into(),
+            parent,
+            collapsed: false,
+        }
+    }
+}
+
+/// Request from header of group
+pub enum GroupAction {
+    /// New group inside
+    Add,
+    MoveUp,
+    MoveDown,
+    /// Remove group, its waves and groups go to parent
+    Ungroup,
+    /// Remove group with all waves
+    Delete,
+    /// Generate files only for waves of group
+    Export,
+}
+
+/// Row of main panel
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Row {
+    Group { id: GroupId, depth: usize },
+    Wave { index: usize, depth: usize },
+}
+
+/// Child of group in place of first wave of it
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Item {
+    Group(GroupId),
+    Wave(usize),
+}
+
+/// Groups of project
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Groups(Vec<WaveGroup>);
+
+impl Groups {
+    pub fn get(&self, id: GroupId) -> Option<&WaveGroup> {
+        self.0.iter().find(|g| g.id == id)
+    }
+
+    pub fn get_mut(&mut self, id: GroupId) -> Option<&mut WaveGroup> {
+        self.0.iter_mut().find(|g| g.id == id)
+    }
+
+    pub fn push(&mut self, group: WaveGroup) {
+        self.0.push(group);
+    }
+
+    /// Existing group, group of removed one is no group
+    fn parent(&self, id: Option<GroupId>) -> Option<GroupId> {
+        id.filter(|id| self.get(*id).is_some())
+    }
+
+    /// Names of group and its parents joined by `.`
+    pub fn path(&self, id: GroupId) -> String {
+        let mut names = Vec::new();
+        let mut current = Some(id);
+        while let Some(g) = current.and_then(|id| self.get(id)) {
+            names.push(g.name.as_str());
+            current = g.parent;
+        }
+        names.reverse();
+        names.join(".")
+    }
+
+    /// `id` is `group` or nested into it
+    pub fn contains(&self, group: GroupId, id: Option<GroupId>) -> bool {
+        let mut current = self.parent(id);
+        while let Some(id) = current {
+            if id == group {
+                return true;
+            }
+            current = self.get(id).and_then(|g| self.parent(g.parent));
+        }
+        false
+    }
+
+    /// Ids of groups in tree order with their paths, for choice of group
+    pub fn paths(&self, waves: &[Wave]) -> Vec<(GroupId, String)> {
+        self.rows(waves, true)
+            .into_iter()
+            .filter_map(|r| match r {
+                Row::Group { id, .. } => Some((id, self.path(id))),
+                Row::Wave { .. } => None,
+            })
+            .collect()
+    }
+
+    /// Group for scope path, missing groups are created
+    pub fn ensure_path(&mut self, path: &[String]) -> Option<GroupId> {
+        let mut parent = None;
+        for name in path {
+            let found = self
+                .0
+                .iter()
+                .find(|g| g.parent == parent && &g.name == name)
+                .map(|g| g.id);
+            parent = Some(found.unwrap_or_else(|| {
+                let g = WaveGroup::new(name.clone(), parent);
+                let id = g.id;
+                self.0.push(g);
+                id
+            }));
+        }
+        parent
+    }
+
+    /// Children of `parent` in order of their first waves, empty groups last
+    fn children(&self, waves: &[Wave], parent: Option<GroupId>) -> Vec<Item> {
+        let mut items: Vec<(usize, Item)> = waves
+            .iter()
+            .enumerate()
+            .filter(|(_, w)| self.parent(w.group()) == parent)
+            .map(|(i, _)| (i, Item::Wave(i)))
+            .collect();
+        for g in self.0.iter().filter(|g| self.parent(g.parent) == parent) {
+            let first = waves
+                .iter()
+                .position(|w| self.contains(g.id, w.group()))
+                .unwrap_or(usize::MAX);
+            items.push((first, Item::Group(g.id)));
+        }
+        items.sort_by_key(|(first, _)| *first);
+        items.into_iter().map(|(_, item)| item).collect()
+    }
+
+    /// Rows of main panel, waves of collapsed groups are skipped unless `all`
+    pub fn rows(&self, waves: &[Wave], all: bool) -> Vec<Row> {
+        let mut rows = Vec::with_capacity(waves.len());
+        self.rows_of(waves, None, 0, all, &mut rows);
+        rows
+    }
+
+    fn rows_of(
+        &self,
+        waves: &[Wave],
+        parent: Option<GroupId>,
+        depth: usize,
+        all: bool,
+        rows: &mut Vec<Row>,
+    ) {
+        for item in self.children(waves, parent) {
+            match item {
+                Item::Wave(index) => rows.push(Row::Wave { index, depth }),
+                Item::Group(id) => {
+                    rows.push(Row::Group { id, depth });
+                    if all || !self.get(id).map_or(false, |g| g.collapsed) {
+                        self.rows_of(waves, Some(id), depth + 1, all, rows);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Indexes of waves in group and its subgroups
+    pub fn waves_of(&self, waves: &[Wave], id: GroupId) -> Vec<usize> {
+        (0..waves.len())
+            .filter(|i| self.contains(id, waves[*i].group()))
+            .collect()
+    }
+
+    /// Move group before previous or after next item of its parent.
+    /// Returns applied moves of waves
+    pub fn move_group(&self, waves: &mut Vec<Wave>, id: GroupId, up: bool) -> Vec<Command> {
+        let Some(group) = self.get(id) else {
+            return Vec::new();
+        };
+        let items = self.children(waves, self.parent(group.parent));
+        let Some(pos) = items.iter().position(|i| *i == Item::Group(id)) else {
+            return Vec::new();
+        };
+        // Item moved up before other one
+        let (mover, anchor) = match up {
+            true if pos > 0 => (items[pos], items[pos - 1]),
+            false if pos + 1 < items.len() => (items[pos + 1], items[pos]),
+            _ => return Vec::new(),
+        };
+        let indexes = |item: Item| match item {
+            Item::Wave(i) => vec![i],
+            Item::Group(g) => self.waves_of(waves, g),
+        };
+        let Some(to) = indexes(anchor).first().copied() else {
+            return Vec::new();
+        };
+        let ids: Vec<WaveId> = indexes(mover).iter().map(|i| waves[*i].id()).collect();
+        let mut commands = Vec::with_capacity(ids.len());
+        for (k, wave) in ids.into_iter().enumerate() {
+            let Some(from) = waves.iter().position(|w| w.id() == wave) else {
+                continue;
+            };
+            commands.push(move_to(waves, from, to + k));
+        }
+        commands
+    }
+
+    /// Indexes of first and last waves of item, `None` for empty group
+    fn bounds(&self, waves: &[Wave], item: Item) -> Option<(usize, usize)> {
+        match item {
+            Item::Wave(i) => Some((i, i)),
+            Item::Group(g) => {
+                let indexes = self.waves_of(waves, g);
+                Some((*indexes.first()?, *indexes.last()?))
+            }
+        }
+    }
+
+    /// Move wave over neighbour items of its group, groups are passed as whole
+    pub fn move_wave(&self, waves: &mut Vec<Wave>, index: usize, to: WaveMove) -> Option<Command> {
+        let items: Vec<Item> = self
+            .children(waves, self.parent(waves.get(index)?.group()))
+            .into_iter()
+            .filter(|i| self.bounds(waves, *i).is_some())
+            .collect();
+        let pos = items.iter().position(|i| *i == Item::Wave(index))?;
+        let target = match to {
+            WaveMove::Up => items.get(pos.checked_sub(1)?)?,
+            WaveMove::Down => items.get(pos + 1)?,
+            WaveMove::Top => items.first()?,
+            WaveMove::Bottom => items.last()?,
+        };
+        let (first, last) = self.bounds(waves, *target)?;
+        let to = if first < index { first } else { last };
+        (to != index).then(|| move_to(waves, index, to))
+    }
+
+    /// Move dragged wave `from` before or `after` row it is dropped on.
+    /// Wave dropped on group header goes into this group
+    pub fn drop_wave(
+        &self,
+        waves: &mut Vec<Wave>,
+        from: usize,
+        target: Row,
+        after: bool,
+    ) -> Vec<Command> {
+        let (group, at) = match target {
+            Row::Wave { index, .. } => (
+                self.parent(waves[index].group()),
+                if after { index + 1 } else { index },
+            ),
+            Row::Group { id, .. } => {
+                let first = self.waves_of(waves, id).first().copied();
+                (Some(id), first.unwrap_or(from))
+            }
+        };
+        let mut commands = Vec::new();
+        if group != self.parent(waves[from].group()) {
+            let edit = waves[from].set_group(group);
+            commands.push(Command::Wave { index: from, edit });
+        }
+        let to = if from < at { at - 1 } else { at };
+        if to != from {
+            commands.push(move_to(waves, from, to));
+        }
+        commands
+    }
+
+    /// Remove group, its children go to its parent. Returns edits of moved waves
+    pub fn ungroup(&mut self, waves: &mut [Wave], id: GroupId) -> Vec<Command> {
+        let Some(pos) = self.0.iter().position(|g| g.id == id) else {
+            return Vec::new();
+        };
+        let group = self.0.remove(pos);
+        for g in self.0.iter_mut().filter(|g| g.parent == Some(id)) {
+            g.parent = group.parent;
+        }
+        waves
+            .iter_mut()
+            .enumerate()
+            .filter(|(_, w)| w.group() == Some(id))
+            .map(|(index, w)| Command::Wave {
+                index,
+                edit: w.set_group(group.parent),
+            })
+            .collect()
+    }
+
+    /// Remove group with subgroups, waves are marked deleted
+    pub fn delete(&mut self, waves: &mut [Wave], id: GroupId) {
+        for i in self.waves_of(waves, id) {
+            waves[i].delete();
+        }
+        let nested: Vec<GroupId> = self
+            .0
+            .iter()
+            .filter(|g| g.id == id || self.contains(id, Some(g.id)))
+            .map(|g| g.id)
+            .collect();
+        self.0.retain(|g| !nested.contains(&g.id));
+    }
+
+    /// Header row of group
+    pub fn header(&mut self, ui: &mut Ui, id: GroupId) -> Option<GroupAction> {
+        let group = self.get_mut(id)?;
+        let mut action = None;
+        ui.horizontal(|ui| {
+            let arrow = if group.collapsed { "⏵" } else { "⏷" };
+            if ui.small_button(arrow).clicked() {
+                group.collapsed = !group.collapsed;
+            }
+            ui.text_edit_singleline(&mut group.name).context_menu(|ui| {
+                if ui.button("Add group").clicked() {
+                    action = Some(GroupAction::Add);
+                    ui.close_menu();
+                }
+                if ui.button("Move up").clicked() {
+                    action = Some(GroupAction::MoveUp);
+                    ui.close_menu();
+                }
+                if ui.button("Move down").clicked() {
+                    action = Some(GroupAction::MoveDown);
+                    ui.close_menu();
+                }
+                if ui.button("Export").clicked() {
+                    action = Some(GroupAction::Export);
+                    ui.close_menu();
+                }
+                hseparator!(ui);
+                if ui.button("Ungroup").clicked() {
+                    action = Some(GroupAction::Ungroup);
+                    ui.close_menu();
+                }
+                if ui.button("Delete with waves").clicked() {
+                    action = Some(GroupAction::Delete);
+                    ui.close_menu();
+                }
+            });
+        });
+        action
+    }
+}
+
+/// Move wave to index `to` of list
+pub fn move_to(waves: &mut Vec<Wave>, from: usize, to: usize) -> Command {
+    let wave = waves.remove(from);
+    waves.insert(to, wave);
+    Command::Move { from, to }
+}
+
+#[cfg(test)]
+mod test {
+    use egui::Vec2;
+
+    use super::{Groups, Row};
+    use crate::app::waves::Wave;
+    use crate::app::waves::WaveMove;
+
+    fn names(groups: &Groups, waves: &[Wave]) -> Vec<String> {
+        groups
+            .rows(waves, false)
+            .into_iter()
+            .map(|r| match r {
+                Row::Group { id, depth } => format!("{}+{}", depth, groups.path(id)),
+                Row::Wave { index, depth } => format!("{}-{}", depth, waves[index].name()),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rows() {
+        let mut groups = Groups::default();
+        let mut waves: Vec<Wave> = ["clk", "a", "b", "x", "c"]
+            .into_iter()
+            .map(|n| Wave::new(n, 4, Vec2::ZERO))
+            .collect();
+        let cpu = groups.ensure_path(&["top".into(), "cpu".into()]);
+        let top = groups.ensure_path(&["top".into()]);
+        assert_eq!(groups.ensure_path(&["top".into(), "cpu".into()]), cpu);
+        waves[1].set_group(cpu);
+        waves[2].set_group(top);
+        waves[4].set_group(cpu);
+        assert_eq!(
+            names(&groups, &waves),
+            ["0-clk", "0+top", "1+top.cpu", "2-a", "2-c", "1-b", "0-x"]
+        );
+
+        let top = top.unwrap();
+        groups.get_mut(top).unwrap().collapsed = true;
+        assert_eq!(names(&groups, &waves), ["0-clk", "0+top", "0-x"]);
+        groups.get_mut(top).unwrap().collapsed = false;
+
+        // Group goes before `clk`, its waves stay together
+        assert_eq!(groups.move_group(&mut waves, top, true).len(), 3);
+        assert_eq!(
+            names(&groups, &waves),
+            ["0+top", "1+top.cpu", "2-a", "2-c", "1-b", "0-clk", "0-x"]
+        );
+        groups.move_group(&mut waves, top, false);
+        assert_eq!(names(&groups, &waves)[0], "0-clk");
+
+        assert_eq!(groups.ungroup(&mut waves, cpu.unwrap()).len(), 2);
+        assert_eq!(
+            names(&groups, &waves),
+            ["0-clk", "0+top", "1-a", "1-b", "1-c", "0-x"]
+        );
+        groups.delete(&mut waves, top);
+        assert_eq!(waves.iter().filter(|w| w.deleted()).count(), 3);
+        assert!(groups.get(top).is_none());
+    }
+
+    #[test]
+    fn test_move_wave() {
+        let mut groups = Groups::default();
+        let mut waves: Vec<Wave> = ["a", "b", "c", "d"]
+            .into_iter()
+            .map(|n| Wave::new(n, 4, Vec2::ZERO))
+            .collect();
+        let g = groups.ensure_path(&["g".into()]);
+        waves[1].set_group(g);
+        waves[2].set_group(g);
+        assert_eq!(names(&groups, &waves), ["0-a", "0+g", "1-b", "1-c", "0-d"]);
+
+        // Group is passed as whole
+        groups.move_wave(&mut waves, 3, WaveMove::Up).unwrap();
+        assert_eq!(names(&groups, &waves), ["0-a", "0-d", "0+g", "1-b", "1-c"]);
+        groups.move_wave(&mut waves, 0, WaveMove::Bottom).unwrap();
+        assert_eq!(names(&groups, &waves), ["0-d", "0+g", "1-b", "1-c", "0-a"]);
+        groups.move_wave(&mut waves, 2, WaveMove::Top).unwrap();
+        assert_eq!(names(&groups, &waves), ["0-d", "0+g", "1-c", "1-b", "0-a"]);
+        assert!(groups.move_wave(&mut waves, 1, WaveMove::Up).is_none());
+
+        // Drop after `a` takes wave out of group, drop on header puts it into group
+        let rows = groups.rows(&waves, false);
+        assert_eq!(groups.drop_wave(&mut waves, 1, rows[4], true).len(), 2);
+        assert_eq!(names(&groups, &waves), ["0-d", "0+g", "1-b", "0-a", "0-c"]);
+        let rows = groups.rows(&waves, false);
+        groups.drop_wave(&mut waves, 0, rows[1], false);
+        assert_eq!(names(&groups, &waves), ["0+g", "1-d", "1-b", "0-a", "0-c"]);
+    }
+}
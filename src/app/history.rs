@@ -0,0 +1,224 @@
+use super::{
+    groups::Groups,
+    waves::{Wave, WaveEdit},
+    windows::ProjectSettings,
+};
+
+/// Max count of commands kept for undo
+const HISTORY_DEPTH: usize = 100;
+
+/// Reversible change of project. Applying command returns command reverting it
+pub enum Command {
+    /// Edit of wave content by index
+    Wave { index: usize, edit: WaveEdit },
+    /// Wave inserted at index
+    Insert { index: usize },
+    /// Wave removed from index
+    Remove { index: usize, wave: Box<Wave> },
+    /// Wave moved to other place of list
+    Move { from: usize, to: usize },
+    /// Other list of groups
+    Groups(Groups),
+    /// Time size of project changed, with other content of all waves
+    Resize {
+        max_time: usize,
+        edits: Vec<WaveEdit>,
+    },
+    /// Commands done by one user action
+    Group(Vec<Command>),
+}
+
+impl Command {
+    fn apply(
+        self,
+        waves: &mut Vec<Wave>,
+        groups: &mut Groups,
+        settings: &mut ProjectSettings,
+    ) -> Command {
+        match self {
+            Command::Wave { index, mut edit } => {
+                if let Some(w) = waves.get_mut(index) {
+                    w.swap_edit(&mut edit);
+                }
+                Command::Wave { index, edit }
+            }
+            Command::Insert { index } => {
+                let mut wave = waves.remove(index);
+                wave.restore();
+                Command::Remove {
+                    index,
+                    wave: Box::new(wave),
+                }
+            }
+            Command::Remove { index, wave } => {
+                waves.insert(index.min(waves.len()), *wave);
+                Command::Insert { index }
+            }
+            Command::Move { from, to } => {
+                if from < waves.len() && to < waves.len() {
+                    let wave = waves.remove(from);
+                    waves.insert(to, wave);
+                }
+                Command::Move { from: to, to: from }
+            }
+            Command::Groups(mut other) => {
+                std::mem::swap(groups, &mut other);
+                Command::Groups(other)
+            }
+            Command::Resize {
+                max_time,
+                mut edits,
+            } => {
+                waves
+                    .iter_mut()
+                    .zip(edits.iter_mut())
+                    .for_each(|(w, e)| w.swap_edit(e));
+                let max_time = std::mem::replace(&mut settings.max_time, max_time);
+                Command::Resize { max_time, edits }
+            }
+            Command::Group(commands) => {
+                let mut reverted: Vec<Command> = commands
+                    .into_iter()
+                    .rev()
+                    .map(|c| c.apply(waves, groups, settings))
+                    .collect();
+                reverted.reverse();
+                Command::Group(reverted)
+            }
+        }
+    }
+}
+
+/// Undo and redo stacks of project commands
+#[derive(Default)]
+pub struct History {
+    undo: Vec<Command>,
+    redo: Vec<Command>,
+}
+
+impl History {
+    /// Command already applied by user. Redo isn't possible after new change
+    pub fn push(&mut self, command: Command) {
+        self.redo.clear();
+        self.undo.push(command);
+        if self.undo.len() > HISTORY_DEPTH {
+            self.undo.remove(0);
+        }
+    }
+
+    /// Commands of one user action, undone together
+    pub fn push_all(&mut self, mut commands: Vec<Command>) {
+        match commands.len() {
+            0 => {}
+            1 => self.push(commands.remove(0)),
+            _ => self.push(Command::Group(commands)),
+        }
+    }
+
+    pub fn undo(
+        &mut self,
+        waves: &mut Vec<Wave>,
+        groups: &mut Groups,
+        settings: &mut ProjectSettings,
+    ) {
+        if let Some(c) = self.undo.pop() {
+            self.redo.push(c.apply(waves, groups, settings));
+        }
+    }
+
+    pub fn redo(
+        &mut self,
+        waves: &mut Vec<Wave>,
+        groups: &mut Groups,
+        settings: &mut ProjectSettings,
+    ) {
+        if let Some(c) = self.redo.pop() {
+            self.undo.push(c.apply(waves, groups, settings));
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use egui::Vec2;
+
+    use super::{Command, History};
+    use crate::app::{
+        groups::{Groups, WaveGroup},
+        waves::Wave,
+        windows::ProjectSettings,
+        WaveType,
+    };
+
+    #[test]
+    fn test_undo_redo() {
+        let mut settings = ProjectSettings::default();
+        let mut waves = vec![Wave::new("a", 16, Vec2::ZERO)];
+        let mut groups = Groups::default();
+        let mut history = History::default();
+
+        waves.push(Wave::new("b", 16, Vec2::ZERO));
+        history.push(Command::Insert { index: 1 });
+
+        let edit = waves[0].content_edit();
+        waves[0].set_type(WaveType::Reg(8));
+        history.push(Command::Wave { index: 0, edit });
+
+        let edits = waves.iter().map(|w| w.content_edit()).collect();
+        waves.iter_mut().for_each(|w| w.set_len(4));
+        history.push(Command::Resize {
+            max_time: settings.max_time,
+            edits,
+        });
+        settings.max_time = 4;
+
+        let wave = waves.remove(0);
+        history.push(Command::Remove {
+            index: 0,
+            wave: Box::new(wave),
+        });
+
+        history.undo(&mut waves, &mut groups, &mut settings);
+        assert_eq!(waves.len(), 2);
+        assert_eq!(waves[0].len(), 4);
+        history.undo(&mut waves, &mut groups, &mut settings);
+        assert_eq!(settings.max_time, 16);
+        assert_eq!((waves[0].len(), waves[0].reg_size()), (16, 8));
+        history.undo(&mut waves, &mut groups, &mut settings);
+        assert_eq!(waves[0].reg_size(), 1);
+        history.undo(&mut waves, &mut groups, &mut settings);
+        assert_eq!(waves.len(), 1);
+        assert!(!history.can_undo());
+
+        history.redo(&mut waves, &mut groups, &mut settings);
+        history.redo(&mut waves, &mut groups, &mut settings);
+        history.redo(&mut waves, &mut groups, &mut settings);
+        assert_eq!((waves.len(), settings.max_time), (2, 4));
+        assert_eq!((waves[0].len(), waves[0].reg_size()), (4, 8));
+
+        let names = |waves: &[Wave]| waves.iter().map(|w| w.name()).collect::<Vec<_>>();
+        let old = groups.clone();
+        groups.push(WaveGroup::new("g", None));
+        let wave = waves.remove(1);
+        waves.insert(0, wave);
+        history.push_all(vec![Command::Groups(old), Command::Move { from: 1, to: 0 }]);
+        history.undo(&mut waves, &mut groups, &mut settings);
+        assert_eq!(names(&waves), ["a", "b"]);
+        assert!(groups.paths(&waves).is_empty());
+        history.redo(&mut waves, &mut groups, &mut settings);
+        assert_eq!(names(&waves), ["b", "a"]);
+        assert_eq!(groups.paths(&waves).len(), 1);
+
+        history.undo(&mut waves, &mut groups, &mut settings);
+        history.push(Command::Insert { index: 2 });
+        assert!(!history.can_redo());
+    }
+}
@@ -0,0 +1,162 @@
+//! Named markers and two measurement cursors on time axis shared by all waves.
+//! Marker and cursor stand at start of cycle, cycle `len` is end of waves.
+
+use egui::{
+    plot::{LineStyle, PlotBounds, PlotPoint, PlotUi, Text, VLine},
+    Color32,
+};
+use serde::{Deserialize, Serialize};
+
+use super::time::TimeScale;
+
+const MARKER_COLOR: Color32 = Color32::from_rgb(230, 140, 30);
+const CURSOR_COLORS: [Color32; 2] = [
+    Color32::from_rgb(40, 180, 80),
+    Color32::from_rgb(40, 160, 220),
+];
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct Marker {
+    pub name: String,
+    pub cycle: usize,
+}
+
+/// How markers go to generated testbench
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MarkerExport {
+    #[default]
+    None,
+    /// Comment with cycle and time of each marker
+    Comment,
+    /// `$display` of marker name at its time
+    Display,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Markers {
+    /// Sorted by cycle
+    pub list: Vec<Marker>,
+    pub cursors: [Option<usize>; 2],
+    /// Cycle to show in center of plots in next frame
+    #[serde(skip)]
+    pub goto: Option<usize>,
+    /// Cycle of last jump, start of search of next and previous marker
+    #[serde(skip)]
+    current: Option<usize>,
+}
+
+impl Markers {
+    /// New marker with default name, returns its index
+    pub fn add(&mut self, cycle: usize) -> usize {
+        let name = format!("marker {}", self.list.len());
+        let index = self.list.partition_point(|m| m.cycle <= cycle);
+        self.list.insert(index, Marker { name, cycle });
+        index
+    }
+
+    pub fn sort(&mut self) {
+        self.list.sort_by_key(|m| m.cycle);
+    }
+
+    /// Cycles between cursors
+    pub fn delta(&self) -> Option<usize> {
+        match self.cursors {
+            [Some(a), Some(b)] => Some(a.abs_diff(b)),
+            _ => None,
+        }
+    }
+
+    /// Text of distance between cursors
+    pub fn delta_text(&self, scale: &TimeScale) -> Option<String> {
+        self.delta()
+            .map(|d| format!("Δ {} cycles, {}", d, scale.format(d as f64)))
+    }
+
+    pub fn jump(&mut self, cycle: usize) {
+        self.goto = Some(cycle);
+        self.current = Some(cycle);
+    }
+
+    /// Jump to first marker after last jump
+    pub fn next(&mut self) {
+        let next = self
+            .list
+            .iter()
+            .find(|m| self.current.map_or(true, |c| m.cycle > c));
+        if let Some(cycle) = next.map(|m| m.cycle) {
+            self.jump(cycle);
+        }
+    }
+
+    /// Jump to last marker before last jump
+    pub fn prev(&mut self) {
+        let prev = self
+            .list
+            .iter()
+            .rfind(|m| self.current.map_or(true, |c| m.cycle < c));
+        if let Some(cycle) = prev.map(|m| m.cycle) {
+            self.jump(cycle);
+        }
+    }
+
+    /// Lines of markers and cursors over wave, with pending jump
+    pub fn draw(&self, plot_ui: &mut PlotUi, top: f64) {
+        if let Some(cycle) = self.goto {
+            let bounds = plot_ui.plot_bounds();
+            let half = bounds.width() / 2.0;
+            let x = cycle as f64;
+            plot_ui.set_plot_bounds(PlotBounds::from_min_max(
+                [x - half, bounds.min()[1]],
+                [x + half, bounds.max()[1]],
+            ));
+        }
+        for m in &self.list {
+            let x = m.cycle as f64;
+            plot_ui.vline(VLine::new(x).color(MARKER_COLOR).name(""));
+            let text = Text::new(PlotPoint::new(x, top), m.name.as_str())
+                .color(MARKER_COLOR)
+                .anchor(egui::Align2::LEFT_TOP);
+            plot_ui.text(text.name(""));
+        }
+        for (cursor, color) in self.cursors.iter().zip(CURSOR_COLORS) {
+            if let Some(c) = cursor {
+                let line = VLine::new(*c as f64)
+                    .color(color)
+                    .style(LineStyle::dashed_loose());
+                plot_ui.vline(line.name(""));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Markers;
+
+    #[test]
+    fn test_markers() {
+        let mut markers = Markers::default();
+        assert_eq!(markers.add(8), 0);
+        assert_eq!(markers.add(2), 0);
+        assert_eq!(markers.add(8), 2);
+        let cycles: Vec<usize> = markers.list.iter().map(|m| m.cycle).collect();
+        assert_eq!(cycles, [2, 8, 8]);
+
+        markers.next();
+        assert_eq!(markers.goto, Some(2));
+        markers.next();
+        assert_eq!(markers.goto, Some(8));
+        markers.next();
+        assert_eq!(markers.goto, Some(8));
+        markers.prev();
+        assert_eq!(markers.goto, Some(2));
+        markers.jump(5);
+        markers.prev();
+        assert_eq!(markers.goto, Some(2));
+
+        assert_eq!(markers.delta(), None);
+        markers.cursors = [Some(7), Some(3)];
+        assert_eq!(markers.delta(), Some(4));
+    }
+}
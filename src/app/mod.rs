@@ -6,15 +6,30 @@ use std::{
 use anyhow::anyhow;
 use egui::{Ui, Vec2};
 
+mod cursor;
+mod groups;
+mod history;
+mod markers;
+mod search;
+mod selection;
+mod time;
 mod waves;
 mod widgets;
 mod windows;
 
-use waves::Wave;
+use cursor::EditCursor;
+use groups::{move_to, GroupAction, GroupId, Groups, Row, WaveGroup};
+use history::{Command, History};
+use markers::Markers;
+use search::Search;
+use selection::RangeSelect;
+use waves::{
+    handle_clipboard, sync_buses, update_clocks, update_derived, Wave, WaveAction, WaveId,
+};
 use zip::write::FileOptions;
 
-use crate::{hseparator, PROJECT_FILE_NAME};
-use windows::{ProjectExport, ProjectSettings};
+use crate::{hseparator, OLD_PROJECT_FILE_NAMES, PROJECT_FILE_NAME};
+use windows::{BusMerge, DomainAssign, GroupAssign, MarkerList, ProjectExport, ProjectSettings};
 
 use self::windows::ImportData;
 pub use waves::WaveType;
@@ -26,6 +41,10 @@ enum AppState {
     ProjectSettings(ProjectSettings),
     ProjectExport(ProjectExport),
     ImportData(ImportData),
+    BusMerge(BusMerge),
+    GroupAssign(GroupAssign),
+    DomainAssign(DomainAssign),
+    Markers(MarkerList),
     Error(anyhow::Error),
 }
 
@@ -42,6 +61,16 @@ pub struct App {
 
     waves: Vec<Wave>,
 
+    groups: Groups,
+
+    markers: Markers,
+
+    #[serde(skip)]
+    search: Search,
+
+    #[serde(skip)]
+    cursor: EditCursor,
+
     #[serde(skip)]
     user_input: egui::InputState,
 
@@ -55,6 +84,20 @@ pub struct App {
 
     #[serde(skip)]
     window_size: Vec2,
+
+    #[serde(skip)]
+    history: History,
+
+    #[serde(skip)]
+    range_select: RangeSelect,
+
+    /// Names of waves in last frame, derived waves are recomputed after rename
+    #[serde(skip)]
+    wave_names: Vec<String>,
+
+    /// Wave moved by its handle
+    #[serde(skip)]
+    dragged: Option<WaveId>,
 }
 
 impl Default for App {
@@ -64,11 +107,19 @@ impl Default for App {
             label: "Hello World!".to_owned(),
             value: 2.7,
             waves: Vec::new(),
+            groups: Groups::default(),
+            markers: Markers::default(),
+            search: Search::default(),
+            cursor: EditCursor::default(),
             user_input: egui::InputState::default(),
             state: AppState::Main,
             project_setting: ProjectSettings::default(),
             window_size: Vec2::ZERO,
             project_file: None,
+            history: History::default(),
+            range_select: RangeSelect::default(),
+            wave_names: Vec::new(),
+            dragged: None,
         }
     }
 }
@@ -97,30 +148,170 @@ impl App {
                 // })
                 // ui.vertical(|ui|{
                 let link_group_id = ui.id().with("link_waves");
-                for wave in &mut self.waves {
-                    wave.current_size.x = ui.available_width();
-                    wave.display(ui, link_group_id, &self.user_input);
-                    let s = ui
-                        .add(egui::Separator::default().horizontal())
-                        .interact(egui::Sense {
-                            click: true,
-                            drag: true,
-                            focusable: true,
-                        });
-                    if s.dragged() {
-                        wave.current_size.y += s.drag_delta().y;
-                        s.on_hover_cursor(egui::CursorIcon::Grabbing);
-                    } else {
-                        s.on_hover_cursor(egui::CursorIcon::Grab);
+                let rows = self.groups.rows(&self.waves, false);
+                // Waves of collapsed groups
+                self.waves.iter_mut().for_each(|w| w.hide());
+                let mut group_actions = Vec::new();
+                let mut row_rects = Vec::with_capacity(rows.len());
+                for row in rows {
+                    let rect = match row {
+                        Row::Group { id, depth } => indented(ui, depth, |ui| {
+                            if let Some(a) = self.groups.header(ui, id) {
+                                group_actions.push((id, a));
+                            }
+                        }),
+                        Row::Wave { index, depth } => indented(ui, depth, |ui| {
+                            let wave = &mut self.waves[index];
+                            wave.current_size.x = ui.available_width();
+                            let scale = &self.project_setting.scale;
+                            wave.display(ui, link_group_id, &self.user_input, &self.markers, scale);
+                            let s = ui.add(egui::Separator::default().horizontal()).interact(
+                                egui::Sense {
+                                    click: true,
+                                    drag: true,
+                                    focusable: true,
+                                },
+                            );
+                            if s.dragged() {
+                                wave.current_size.y += s.drag_delta().y;
+                                s.on_hover_cursor(egui::CursorIcon::Grabbing);
+                            } else {
+                                s.on_hover_cursor(egui::CursorIcon::Grab);
+                            }
+                        }),
+                    };
+                    row_rects.push((row, rect));
+                }
+                self.markers.goto = None;
+                self.cursor.click(&self.waves, &self.user_input);
+                if let Some(cycle) = self.cursor.draw(ui, &mut self.waves) {
+                    self.markers.goto = Some(cycle);
+                }
+                if self.dragged.is_some() {
+                    if let Some((_, rect, after)) = self.drop_target(&row_rects) {
+                        let y = if after { rect.bottom() } else { rect.top() };
+                        let stroke = ui.visuals().selection.stroke;
+                        ui.painter().hline(rect.x_range(), y, stroke);
+                    }
+                }
+                self.range_select.update(&mut self.waves, &self.user_input);
+                handle_clipboard(&mut self.waves, ui, &self.user_input);
+                // Value saved in edit window of one wave goes to all selected waves
+                let batch = self
+                    .waves
+                    .iter_mut()
+                    .enumerate()
+                    .find_map(|(i, w)| w.take_batch_value().map(|v| (i, v)));
+                if let Some((from, v)) = batch {
+                    for (i, w) in self.waves.iter_mut().enumerate() {
+                        if i != from {
+                            w.fill_selection(&v);
+                        }
                     }
                 }
-                self.waves.retain(|v| !v.deleted());
-                if ui.button("Add").clicked() {
-                    self.waves.push(Wave::new(
-                        format!("Wire {}", self.waves.len()),
-                        self.project_setting.max_time,
-                        Vec2::new(ui.available_width(), self.window_size.y / 10.0),
-                    ));
+                sync_buses(&mut self.waves, false);
+                let edits = self.waves.iter_mut().enumerate().flat_map(|(index, w)| {
+                    w.take_edits()
+                        .into_iter()
+                        .map(move |edit| Command::Wave { index, edit })
+                });
+                let mut commands: Vec<Command> = edits.collect();
+                let actions: Vec<(usize, WaveAction)> = self
+                    .waves
+                    .iter_mut()
+                    .enumerate()
+                    .filter_map(|(i, w)| w.take_action().map(|a| (i, a)))
+                    .collect();
+                // From last wave, so inserted waves don't move index of other actions
+                for (i, action) in actions.into_iter().rev() {
+                    match action {
+                        WaveAction::Split(new_waves) => {
+                            for (k, w) in new_waves.into_iter().enumerate() {
+                                self.waves.insert(i + 1 + k, w);
+                                commands.push(Command::Insert { index: i + 1 + k });
+                            }
+                        }
+                        WaveAction::Merge => {
+                            self.state = AppState::BusMerge(BusMerge::new(&self.waves, i));
+                        }
+                        WaveAction::Group => {
+                            self.state = AppState::GroupAssign(GroupAssign::new(
+                                &self.waves,
+                                &self.groups,
+                                i,
+                            ));
+                        }
+                        WaveAction::Domain => {
+                            self.state = AppState::DomainAssign(DomainAssign::new(&self.waves, i));
+                        }
+                        WaveAction::Move(to) => {
+                            commands.extend(self.groups.move_wave(&mut self.waves, i, to));
+                        }
+                        WaveAction::Cycles(edit) => {
+                            let max_time = self.project_setting.max_time;
+                            commands.push(Command::Resize {
+                                max_time,
+                                edits: self.waves.iter().map(|w| w.content_edit()).collect(),
+                            });
+                            let len = edit.new_len(max_time);
+                            for w in self.waves.iter_mut() {
+                                w.edit_cycles(&edit, false);
+                                w.fit_len(len);
+                            }
+                            self.project_setting.max_time = len;
+                        }
+                        WaveAction::Marker(cycle) => {
+                            self.markers.add(cycle);
+                        }
+                        WaveAction::Cursor(cursor, cycle) => {
+                            self.markers.cursors[cursor] = Some(cycle);
+                        }
+                        WaveAction::Drag => self.dragged = Some(self.waves[i].id()),
+                        WaveAction::Drop => {
+                            self.dragged = None;
+                            if let Some((row, _, after)) = self.drop_target(&row_rects) {
+                                let (waves, groups) = (&mut self.waves, &self.groups);
+                                commands.extend(groups.drop_wave(waves, i, row, after));
+                            }
+                        }
+                    }
+                }
+                for (id, action) in group_actions {
+                    self.group_action(id, action, &mut commands);
+                }
+                while let Some(index) = self.waves.iter().position(|v| v.deleted()) {
+                    let mut wave = self.waves.remove(index);
+                    wave.restore();
+                    commands.push(Command::Remove {
+                        index,
+                        wave: Box::new(wave),
+                    });
+                }
+                let mut changed = !commands.is_empty();
+                self.history.push_all(commands);
+                ui.horizontal(|ui| {
+                    if ui.button("Add").clicked() {
+                        self.waves.push(Wave::new(
+                            format!("Wire {}", self.waves.len()),
+                            self.project_setting.max_time,
+                            Vec2::new(ui.available_width(), self.window_size.y / 10.0),
+                        ));
+                        self.history.push(Command::Insert {
+                            index: self.waves.len() - 1,
+                        });
+                    }
+                    if ui.button("Add group").clicked() {
+                        let old = self.groups.clone();
+                        self.groups.push(WaveGroup::new("group", None));
+                        self.history.push(Command::Groups(old));
+                    }
+                });
+                let names: Vec<String> = self.waves.iter().map(|w| w.name()).collect();
+                changed |= names != self.wave_names;
+                if changed {
+                    update_derived(&mut self.waves);
+                    update_clocks(&mut self.waves);
+                    self.wave_names = names;
                 }
             });
     }
@@ -132,6 +323,10 @@ impl App {
             AppState::ProjectExport(_) => self.draw_state_export(ctx, frame),
             AppState::Error(_) => self.draw_state_error(ctx, frame),
             AppState::ImportData(_) => self.draw_state_import_data(ctx, frame),
+            AppState::BusMerge(_) => self.draw_state_bus_merge(ctx, frame),
+            AppState::GroupAssign(_) => self.draw_state_group_assign(ctx, frame),
+            AppState::DomainAssign(_) => self.draw_state_domain_assign(ctx, frame),
+            AppState::Markers(_) => self.draw_state_markers(ctx),
         }
     }
 
@@ -143,7 +338,20 @@ impl App {
         match settings.display(ctx, frame) {
             windows::WindowResult::Open => {}
             windows::WindowResult::Save => {
-                match settings.generate_data(&self.waves) {
+                let rows = self.groups.rows(&self.waves, true);
+                let waves: Vec<&Wave> = rows
+                    .into_iter()
+                    .filter_map(|r| match r {
+                        Row::Wave { index, .. } => Some(&self.waves[index]),
+                        Row::Group { .. } => None,
+                    })
+                    .filter(|w| match &settings.group {
+                        Some((g, _)) => self.groups.contains(*g, w.group()),
+                        None => true,
+                    })
+                    .collect();
+                let scale = &self.project_setting.scale;
+                match settings.generate_data(&waves, &self.groups, &self.markers, scale) {
                     Ok(()) => self.state = AppState::Main,
                     Err(e) => self.state = AppState::Error(e),
                 };
@@ -166,11 +374,18 @@ impl App {
             windows::WindowResult::Open => {}
             windows::WindowResult::Save => {
                 if settings.max_time != self.project_setting.max_time {
+                    self.history.push(Command::Resize {
+                        max_time: self.project_setting.max_time,
+                        edits: self.waves.iter().map(|w| w.content_edit()).collect(),
+                    });
                     self.waves.iter_mut().for_each(|w| {
                         w.set_len(settings.max_time);
                     });
                     self.project_setting.max_time = settings.max_time;
+                    update_derived(&mut self.waves);
+                    update_clocks(&mut self.waves);
                 }
+                self.project_setting.scale = settings.scale;
                 self.state = AppState::Main;
             }
             windows::WindowResult::Cancel | windows::WindowResult::Close => {
@@ -205,7 +420,22 @@ impl App {
         };
         match import.display(ctx, _frame) {
             windows::WindowResult::Open => {},
-            windows::WindowResult::Save => todo!(),
+            windows::WindowResult::Save => {
+                let old = self.groups.clone();
+                let mut commands = vec![Command::Groups(old)];
+                for (mut wave, scope) in import.take_waves() {
+                    let group = self.groups.ensure_path(&scope);
+                    wave.set_group(group);
+                    wave.fit_len(self.project_setting.max_time);
+                    wave.current_size = Vec2::new(self.window_size.x, self.window_size.y / 10.0);
+                    self.waves.push(wave);
+                    commands.push(Command::Insert {
+                        index: self.waves.len() - 1,
+                    });
+                }
+                self.history.push_all(commands);
+                self.state = AppState::Main;
+            }
             windows::WindowResult::Cancel | windows::WindowResult::Close => {self.state = AppState::Main},
             windows::WindowResult::Error(e)=>{
                 self.state = AppState::Error(e);
@@ -213,6 +443,194 @@ impl App {
         }
     }
 
+    fn draw_state_bus_merge(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        let AppState::BusMerge(merge) = &mut self.state else {
+            self.state = AppState::Main;
+            return;
+        };
+        match merge.display(ctx, frame) {
+            windows::WindowResult::Open => {}
+            windows::WindowResult::Save => {
+                let chosen = merge.chosen();
+                let parts: Vec<usize> = (0..self.waves.len())
+                    .filter(|i| chosen.contains(&self.waves[*i].id()))
+                    .collect();
+                let bus = Wave::merge(
+                    &merge.name,
+                    &parts.iter().map(|i| &self.waves[*i]).collect::<Vec<_>>(),
+                );
+                let mut commands = Vec::new();
+                if merge.linked {
+                    let mut lsb = bus.reg_size();
+                    for index in parts.iter().copied() {
+                        let size = self.waves[index].reg_size();
+                        lsb -= size;
+                        let edit = self.waves[index].link(bus.id(), lsb + size - 1, lsb);
+                        commands.push(Command::Wave { index, edit });
+                    }
+                }
+                let index = parts.first().copied().unwrap_or(self.waves.len());
+                self.waves.insert(index, bus);
+                commands.push(Command::Insert { index });
+                self.history.push_all(commands);
+                self.state = AppState::Main;
+            }
+            windows::WindowResult::Cancel | windows::WindowResult::Close => {
+                self.state = AppState::Main;
+            }
+            windows::WindowResult::Error(e) => {
+                self.state = AppState::Error(e);
+            }
+        }
+    }
+
+    fn draw_state_group_assign(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        let AppState::GroupAssign(assign) = &mut self.state else {
+            self.state = AppState::Main;
+            return;
+        };
+        match assign.display(ctx, frame) {
+            windows::WindowResult::Open => {}
+            windows::WindowResult::Save => {
+                let Some(index) = self.waves.iter().position(|w| w.id() == assign.wave) else {
+                    self.state = AppState::Main;
+                    return;
+                };
+                let mut commands = Vec::new();
+                let mut group = assign.group;
+                if !assign.new_group.trim().is_empty() {
+                    commands.push(Command::Groups(self.groups.clone()));
+                    let g = WaveGroup::new(assign.new_group.trim(), group);
+                    group = Some(g.id);
+                    self.groups.push(g);
+                }
+                let edit = self.waves[index].set_group(group);
+                commands.push(Command::Wave { index, edit });
+                // Wave goes after last wave of its new group
+                let last = group.and_then(|g| {
+                    let waves = self.groups.waves_of(&self.waves, g);
+                    waves.into_iter().rfind(|i| *i != index)
+                });
+                let to = last.map(|l| if l > index { l } else { l + 1 });
+                if let Some(to) = to.filter(|to| *to != index) {
+                    commands.push(move_to(&mut self.waves, index, to));
+                }
+                self.history.push_all(commands);
+                self.state = AppState::Main;
+            }
+            windows::WindowResult::Cancel | windows::WindowResult::Close => {
+                self.state = AppState::Main;
+            }
+            windows::WindowResult::Error(e) => {
+                self.state = AppState::Error(e);
+            }
+        }
+    }
+
+    fn draw_state_domain_assign(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        let AppState::DomainAssign(assign) = &mut self.state else {
+            self.state = AppState::Main;
+            return;
+        };
+        match assign.display(ctx, frame) {
+            windows::WindowResult::Open => {}
+            windows::WindowResult::Save => {
+                if let Some(index) = self.waves.iter().position(|w| w.id() == assign.wave) {
+                    let edit = self.waves[index].set_domain(assign.clock);
+                    self.history.push(Command::Wave { index, edit });
+                    update_clocks(&mut self.waves);
+                }
+                self.state = AppState::Main;
+            }
+            windows::WindowResult::Cancel | windows::WindowResult::Close => {
+                self.state = AppState::Main;
+            }
+            windows::WindowResult::Error(e) => {
+                self.state = AppState::Error(e);
+            }
+        }
+    }
+
+    fn draw_state_markers(&mut self, ctx: &egui::Context) {
+        let AppState::Markers(list) = &mut self.state else {
+            self.state = AppState::Main;
+            return;
+        };
+        match list.display(ctx, &mut self.markers) {
+            windows::WindowResult::Open | windows::WindowResult::Save => {}
+            windows::WindowResult::Cancel | windows::WindowResult::Close => {
+                self.state = AppState::Main;
+            }
+            windows::WindowResult::Error(e) => {
+                self.state = AppState::Error(e);
+            }
+        }
+    }
+
+    /// Indexes of waves in main panel from top to bottom, without collapsed ones
+    fn shown_waves(&self) -> Vec<usize> {
+        let rows = self.groups.rows(&self.waves, false);
+        rows.into_iter()
+            .filter_map(|r| match r {
+                Row::Wave { index, .. } => Some(index),
+                Row::Group { .. } => None,
+            })
+            .collect()
+    }
+
+    /// Row under pointer for dragged wave and whether wave goes after it
+    fn drop_target(&self, rows: &[(Row, egui::Rect)]) -> Option<(Row, egui::Rect, bool)> {
+        let y = self.user_input.pointer.interact_pos()?.y;
+        let (row, rect) = rows
+            .iter()
+            .find(|(_, r)| y < r.bottom())
+            .or_else(|| rows.last())?;
+        Some((*row, *rect, y > rect.center().y))
+    }
+
+    /// Action requested by header of group, done commands are added to `commands`
+    fn group_action(&mut self, id: GroupId, action: GroupAction, commands: &mut Vec<Command>) {
+        match action {
+            GroupAction::Add => {
+                commands.push(Command::Groups(self.groups.clone()));
+                self.groups.push(WaveGroup::new("group", Some(id)));
+            }
+            GroupAction::MoveUp | GroupAction::MoveDown => {
+                let up = matches!(action, GroupAction::MoveUp);
+                commands.extend(self.groups.move_group(&mut self.waves, id, up));
+            }
+            GroupAction::Ungroup => {
+                commands.push(Command::Groups(self.groups.clone()));
+                commands.extend(self.groups.ungroup(&mut self.waves, id));
+            }
+            GroupAction::Delete => {
+                commands.push(Command::Groups(self.groups.clone()));
+                self.groups.delete(&mut self.waves, id);
+            }
+            GroupAction::Export => {
+                let mut export = ProjectExport::default();
+                export.group = Some((id, self.groups.path(id)));
+                self.state = AppState::ProjectExport(export);
+            }
+        }
+    }
+
+    fn undo(&mut self) {
+        let (waves, groups) = (&mut self.waves, &mut self.groups);
+        self.history.undo(waves, groups, &mut self.project_setting);
+        sync_buses(&mut self.waves, true);
+        update_derived(&mut self.waves);
+        update_clocks(&mut self.waves);
+    }
+
+    fn redo(&mut self) {
+        let (waves, groups) = (&mut self.waves, &mut self.groups);
+        self.history.redo(waves, groups, &mut self.project_setting);
+        sync_buses(&mut self.waves, true);
+        update_derived(&mut self.waves);
+        update_clocks(&mut self.waves);
+    }
+
     //TODO: Rewrite to ->Result
     fn save_to_file(&mut self)->Result<(), anyhow::Error> {
         if self.project_file.is_none() {
@@ -246,7 +664,11 @@ impl App {
                 return;
             }
         };
-        let zf = match zip.by_name(PROJECT_FILE_NAME) {
+        let name = std::iter::once(PROJECT_FILE_NAME)
+            .chain(OLD_PROJECT_FILE_NAMES.iter().copied())
+            .find(|n| zip.file_names().any(|f| f == *n))
+            .unwrap_or(PROJECT_FILE_NAME);
+        let zf = match zip.by_name(name) {
             Ok(zf) => zf,
             Err(e) => {
                 self.state = AppState::Error(anyhow!("This is not wave gen project").context(e));
@@ -273,14 +695,52 @@ impl eframe::App for App {
     /// Put your widgets into a `SidePanel`, `TopPanel`, `CentralPanel`, `Window` or `Area`.
     fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
         // let Self { label, value, waves, max_time } = self;
+        let focused = ctx.memory(|m| m.focus().is_some());
+        let shown = self.shown_waves();
         ctx.input_mut(|i| {
             if i.consume_key(egui::Modifiers::CTRL, egui::Key::S) {
                 if let Err(e) = self.save_to_file(){
                     self.state = AppState::Error(e);
                 };
             }
+            if i.consume_key(egui::Modifiers::CTRL, egui::Key::F) {
+                if self.search.open {
+                    self.search.close(&mut self.waves);
+                } else {
+                    self.search.open = true;
+                }
+            }
+            if !focused {
+                self.cursor.keys(i, &mut self.waves, &shown);
+            }
             self.user_input = i.clone();
         });
+        // Text fields have own undo
+        if ctx.memory(|m| m.focus().is_none()) {
+            let (undo, redo) = ctx.input_mut(|i| {
+                let redo =
+                    i.consume_key(egui::Modifiers::CTRL | egui::Modifiers::SHIFT, egui::Key::Z);
+                (i.consume_key(egui::Modifiers::CTRL, egui::Key::Z), redo)
+            });
+            if undo {
+                self.undo();
+            }
+            if redo {
+                self.redo();
+            }
+            let (prev, next) = ctx.input_mut(|i| {
+                (
+                    i.consume_key(egui::Modifiers::CTRL, egui::Key::ArrowLeft),
+                    i.consume_key(egui::Modifiers::CTRL, egui::Key::ArrowRight),
+                )
+            });
+            if prev {
+                self.markers.prev();
+            }
+            if next {
+                self.markers.next();
+            }
+        }
 
         // Examples of how to create different panels and windows.
         // Pick whichever suits you.
@@ -313,6 +773,39 @@ impl eframe::App for App {
                         frame.close();
                     }
                 });
+                ui.menu_button("Edit", |ui| {
+                    let undo = egui::Button::new("Undo").shortcut_text("Ctrl+Z");
+                    if ui.add_enabled(self.history.can_undo(), undo).clicked() {
+                        self.undo();
+                        ui.close_menu();
+                    }
+                    let redo = egui::Button::new("Redo").shortcut_text("Ctrl+Shift+Z");
+                    if ui.add_enabled(self.history.can_redo(), redo).clicked() {
+                        self.redo();
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("View", |ui| {
+                    if ui.button("Markers").clicked() {
+                        let list = MarkerList::new(self.project_setting);
+                        self.state = AppState::Markers(list);
+                        ui.close_menu();
+                    }
+                    let prev = egui::Button::new("Previous marker").shortcut_text("Ctrl+Left");
+                    if ui.add(prev).clicked() {
+                        self.markers.prev();
+                    }
+                    let next = egui::Button::new("Next marker").shortcut_text("Ctrl+Right");
+                    if ui.add(next).clicked() {
+                        self.markers.next();
+                    }
+                    hseparator!(ui);
+                    let find = egui::Button::new("Find").shortcut_text("Ctrl+F");
+                    if ui.add(find).clicked() {
+                        self.search.open = true;
+                        ui.close_menu();
+                    }
+                });
                 ui.menu_button("Project", |ui| {
                     if ui.button("Import").clicked(){
                         self.state = AppState::ImportData(ImportData::default());
@@ -325,9 +818,21 @@ impl eframe::App for App {
                         self.state = AppState::ProjectSettings(self.project_setting);
                     }
                 });
+                if let Some(delta) = self.markers.delta_text(&self.project_setting.scale) {
+                    ui.separator();
+                    ui.label(delta);
+                }
             });
         });
 
+        if self.search.open {
+            egui::TopBottomPanel::bottom("find_bar").show(ctx, |ui| {
+                if let Some(cycle) = self.search.bar(ui, &mut self.waves) {
+                    self.markers.jump(cycle);
+                }
+            });
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| {
             self.central_panel(ui);
         });
@@ -335,3 +840,17 @@ impl eframe::App for App {
         self.draw_state(ctx, frame);
     }
 }
+
+/// Content shifted right by nesting `depth` of groups. Returns rect of content
+fn indented<F: FnOnce(&mut Ui)>(ui: &mut Ui, depth: usize, f: F) -> egui::Rect {
+    if depth == 0 {
+        ui.vertical(f).response.rect
+    } else {
+        ui.horizontal(|ui| {
+            ui.add_space(depth as f32 * 16.0);
+            ui.vertical(f);
+        })
+        .response
+        .rect
+    }
+}
@@ -0,0 +1,232 @@
+//! Find bar: search of cycles in one wave or in all waves with selected cycles,
+//! jumps between starts of found runs.
+
+use egui::Ui;
+
+use super::waves::{Query, Wave, WaveId};
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+enum SearchKind {
+    #[default]
+    Equal,
+    Differ,
+    Within,
+    Rising,
+    Falling,
+    Change,
+}
+
+impl SearchKind {
+    const ALL: [SearchKind; 6] = [
+        SearchKind::Equal,
+        SearchKind::Differ,
+        SearchKind::Within,
+        SearchKind::Rising,
+        SearchKind::Falling,
+        SearchKind::Change,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            SearchKind::Equal => "equals",
+            SearchKind::Differ => "differs from",
+            SearchKind::Within => "within",
+            SearchKind::Rising => "rising edge",
+            SearchKind::Falling => "falling edge",
+            SearchKind::Change => "changes",
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Search {
+    pub open: bool,
+    /// Searched wave, `None` for all waves with selected cycles
+    wave: Option<WaveId>,
+    kind: SearchKind,
+    value: String,
+    high: String,
+    /// Sorted starts of found runs in all searched waves
+    matches: Vec<usize>,
+    /// Cycle of last jump
+    current: Option<usize>,
+    error: Option<String>,
+}
+
+impl Search {
+    fn query(&self) -> Query {
+        match self.kind {
+            SearchKind::Equal => Query::Equal(self.value.clone()),
+            SearchKind::Differ => Query::Differ(self.value.clone()),
+            SearchKind::Within => Query::Within(self.value.clone(), self.high.clone()),
+            SearchKind::Rising => Query::Rising,
+            SearchKind::Falling => Query::Falling,
+            SearchKind::Change => Query::Change,
+        }
+    }
+
+    /// Search again and highlight found cycles, other waves lose highlight
+    pub fn update(&mut self, waves: &mut [Wave]) {
+        let query = self.query();
+        let needs_value = !matches!(query, Query::Rising | Query::Falling | Query::Change);
+        self.matches.clear();
+        self.error = None;
+        for w in waves.iter_mut() {
+            let searched = match self.wave {
+                Some(id) => w.id() == id,
+                None => w.has_selection(),
+            };
+            if !searched || (needs_value && self.value.trim().is_empty()) {
+                w.set_found(Vec::new());
+                continue;
+            }
+            match w.find(&query) {
+                Ok(found) => {
+                    self.matches.extend(found.iter().map(|r| r.start));
+                    w.set_found(found);
+                }
+                Err(e) => {
+                    self.error.get_or_insert(e);
+                    w.set_found(Vec::new());
+                }
+            }
+        }
+        self.matches.sort_unstable();
+        self.matches.dedup();
+    }
+
+    /// Highlight of all waves removed
+    pub fn close(&mut self, waves: &mut [Wave]) {
+        self.open = false;
+        waves.iter_mut().for_each(|w| w.set_found(Vec::new()));
+    }
+
+    /// First match after last jump
+    fn next(&mut self) -> Option<usize> {
+        let next = self
+            .matches
+            .iter()
+            .find(|m| self.current.map_or(true, |c| **m > c))
+            .copied();
+        self.current = next.or(self.current);
+        next
+    }
+
+    /// Last match before last jump
+    fn prev(&mut self) -> Option<usize> {
+        let prev = self
+            .matches
+            .iter()
+            .rfind(|m| self.current.map_or(true, |c| **m < c))
+            .copied();
+        self.current = prev.or(self.current);
+        prev
+    }
+
+    /// Bar of search, returns cycle to jump to
+    pub fn bar(&mut self, ui: &mut Ui, waves: &mut [Wave]) -> Option<usize> {
+        let mut changed = false;
+        let mut jump = None;
+        ui.horizontal(|ui| {
+            let name = self
+                .wave
+                .and_then(|id| waves.iter().find(|w| w.id() == id))
+                .map_or("Selected waves".to_string(), |w| w.name());
+            egui::ComboBox::new("search_wave", "")
+                .selected_text(name)
+                .show_ui(ui, |ui| {
+                    let r = ui.selectable_value(&mut self.wave, None, "Selected waves");
+                    changed |= r.changed();
+                    for w in waves.iter() {
+                        let r = ui.selectable_value(&mut self.wave, Some(w.id()), w.name());
+                        changed |= r.changed();
+                    }
+                });
+            egui::ComboBox::new("search_kind", "")
+                .selected_text(self.kind.label())
+                .show_ui(ui, |ui| {
+                    for kind in SearchKind::ALL {
+                        let r = ui.selectable_value(&mut self.kind, kind, kind.label());
+                        changed |= r.changed();
+                    }
+                });
+            let mut enter = false;
+            if matches!(
+                self.kind,
+                SearchKind::Equal | SearchKind::Differ | SearchKind::Within
+            ) {
+                let edit = egui::TextEdit::singleline(&mut self.value)
+                    .hint_text("8'hff")
+                    .desired_width(120.0);
+                let r = ui.add(edit);
+                changed |= r.changed();
+                enter |= r.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            }
+            if self.kind == SearchKind::Within {
+                ui.label("..");
+                let edit = egui::TextEdit::singleline(&mut self.high).desired_width(120.0);
+                let r = ui.add(edit);
+                changed |= r.changed();
+                enter |= r.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            }
+            let prev = ui.button("⏶ Previous").clicked();
+            let next = ui.button("⏷ Next").clicked() || enter;
+            if changed || prev || next {
+                self.update(waves);
+            }
+            if prev {
+                jump = self.prev();
+            } else if next {
+                jump = self.next();
+            }
+            match &self.error {
+                Some(e) => ui.colored_label(egui::Color32::RED, e),
+                None => ui.label(format!("{} found", self.matches.len())),
+            };
+            if ui.button("✖").clicked() {
+                self.close(waves);
+            }
+        });
+        jump
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use egui::Vec2;
+
+    use super::{Search, SearchKind};
+    use crate::app::waves::{BitValue, Wave};
+
+    #[test]
+    fn test_jumps() {
+        let mut waves = vec![Wave::new("a", 8, Vec2::ZERO), Wave::new("b", 8, Vec2::ZERO)];
+        let one = BitValue::from_u64(1, 1);
+        for range in [2..=3, 6..=6] {
+            waves[0].select(range);
+            waves[0].fill_selection(&one);
+        }
+        waves[0].select(0..=7);
+        let mut search = Search {
+            kind: SearchKind::Equal,
+            value: "1".into(),
+            ..Default::default()
+        };
+        search.update(&mut waves);
+        assert_eq!(search.matches, [2, 6]);
+        assert_eq!(search.next(), Some(2));
+        assert_eq!(search.next(), Some(6));
+        assert_eq!(search.next(), None);
+        assert_eq!(search.prev(), Some(2));
+
+        search.kind = SearchKind::Rising;
+        search.wave = Some(waves[1].id());
+        search.update(&mut waves);
+        assert!(search.matches.is_empty());
+        search.wave = Some(waves[0].id());
+        search.update(&mut waves);
+        assert_eq!(search.matches, [2, 6]);
+        search.close(&mut waves);
+        assert!(!search.open);
+    }
+}
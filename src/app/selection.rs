@@ -0,0 +1,62 @@
+use egui::InputState;
+
+use super::waves::Wave;
+
+/// Selection of time span over several waves. Ctrl+drag selects rectangle of waves and
+/// cycles, Shift+click or Shift+drag extends it from last clicked cycle
+#[derive(Default)]
+pub struct RangeSelect {
+    /// Wave index and cycle where selection starts
+    anchor: Option<(usize, usize)>,
+    dragging: bool,
+}
+
+impl RangeSelect {
+    pub fn update(&mut self, waves: &mut [Wave], input: &InputState) {
+        let pointer = &input.pointer;
+        if !pointer.primary_down() {
+            self.dragging = false;
+        }
+        let Some(pos) = pointer.interact_pos() else {
+            return;
+        };
+        let Some((wave, cycle)) = waves
+            .iter()
+            .enumerate()
+            .find_map(|(i, w)| w.cycle_at(pos).map(|c| (i, c)))
+        else {
+            return;
+        };
+        let (ctrl, shift) = (input.modifiers.ctrl, input.modifiers.shift);
+        if pointer.primary_pressed() {
+            if !waves[wave].plot_contains(pos) {
+                return;
+            }
+            if !shift || self.anchor.is_none() {
+                self.anchor = Some((wave, cycle));
+            }
+            self.dragging = ctrl || shift;
+            if !self.dragging {
+                return;
+            }
+        } else if !self.dragging {
+            return;
+        }
+        if let Some(anchor) = self.anchor {
+            select(waves, anchor, (wave, cycle));
+        }
+    }
+}
+
+/// Select cycles between `from` and `to` in all waves between them, clear other waves
+fn select(waves: &mut [Wave], from: (usize, usize), to: (usize, usize)) {
+    let rows = from.0.min(to.0)..=from.0.max(to.0);
+    let cycles = from.1.min(to.1)..=from.1.max(to.1);
+    for (i, w) in waves.iter_mut().enumerate() {
+        if rows.contains(&i) {
+            w.select(cycles.clone());
+        } else {
+            w.clear_selection();
+        }
+    }
+}
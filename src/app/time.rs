@@ -0,0 +1,165 @@
+//! Physical time of cycles: verilog timescale and duration of one cycle.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum TimeUnit {
+    S,
+    Ms,
+    Us,
+    Ns,
+    Ps,
+    Fs,
+}
+
+impl TimeUnit {
+    pub const ALL: [TimeUnit; 6] = [
+        TimeUnit::S,
+        TimeUnit::Ms,
+        TimeUnit::Us,
+        TimeUnit::Ns,
+        TimeUnit::Ps,
+        TimeUnit::Fs,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TimeUnit::S => "s",
+            TimeUnit::Ms => "ms",
+            TimeUnit::Us => "us",
+            TimeUnit::Ns => "ns",
+            TimeUnit::Ps => "ps",
+            TimeUnit::Fs => "fs",
+        }
+    }
+
+    /// Power of ten of unit in seconds
+    fn exp(&self) -> i32 {
+        match self {
+            TimeUnit::S => 0,
+            TimeUnit::Ms => -3,
+            TimeUnit::Us => -6,
+            TimeUnit::Ns => -9,
+            TimeUnit::Ps => -12,
+            TimeUnit::Fs => -15,
+        }
+    }
+}
+
+/// Timescale of testbench and duration of one cycle
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct TimeScale {
+    pub unit: TimeUnit,
+    pub precision: TimeUnit,
+    /// Duration of one cycle in time units
+    pub step: f64,
+}
+
+impl Default for TimeScale {
+    /// Cycle of 2 time units, same as half period delay `#1` of old testbench
+    fn default() -> Self {
+        Self {
+            unit: TimeUnit::Ns,
+            precision: TimeUnit::Ps,
+            step: 2.0,
+        }
+    }
+}
+
+impl TimeScale {
+    /// Time of `cycles` in time units
+    pub fn time(&self, cycles: f64) -> f64 {
+        cycles * self.step
+    }
+
+    /// Time of `cycles` in largest unit keeping it at least 1, like `1.5 us`
+    pub fn format(&self, cycles: f64) -> String {
+        let time = self.time(cycles);
+        if time == 0.0 {
+            return format!("0 {}", self.unit.name());
+        }
+        let seconds = time * 10f64.powi(self.unit.exp());
+        let unit = TimeUnit::ALL
+            .into_iter()
+            .find(|u| seconds.abs() >= 10f64.powi(u.exp()) * 0.9999)
+            .unwrap_or(TimeUnit::Fs);
+        let v = seconds / 10f64.powi(unit.exp());
+        format!("{} {}", (v * 1000.0).round() / 1000.0, unit.name())
+    }
+
+    /// Cycles of time like `20`, `20ns` or `1.5 us`. Number without unit is in time units
+    pub fn parse(&self, s: &str) -> Option<f64> {
+        let s = s.trim();
+        let split = s.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(s.len());
+        let (num, unit) = s.split_at(split);
+        let mut time: f64 = num.trim().parse().ok()?;
+        if !unit.is_empty() {
+            let unit = TimeUnit::ALL.into_iter().find(|u| u.name() == unit)?;
+            time *= 10f64.powi(unit.exp() - self.unit.exp());
+        }
+        Some(time / self.step)
+    }
+
+    /// Verilog delay of `cycles` in time units rounded to precision, like `2.5`
+    pub fn delay(&self, cycles: f64) -> String {
+        let digits = (self.unit.exp() - self.precision.exp()).max(0);
+        let scale = 10f64.powi(digits);
+        let v = (self.time(cycles) * scale).round() / scale;
+        format!("{}", v)
+    }
+
+    /// Precision must not be coarser than unit, half of cycle must be
+    /// representable in precision for clock of testbench
+    pub fn check(&self) -> Result<(), String> {
+        if self.precision < self.unit {
+            return Err("Precision must not be coarser than time unit".into());
+        }
+        if !(self.step > 0.0 && self.step.is_finite()) {
+            return Err("Cycle duration must be positive".into());
+        }
+        let half = self.step / 2.0 * 10f64.powi(self.unit.exp() - self.precision.exp());
+        if (half - half.round()).abs() > 1e-6 {
+            return Err(format!(
+                "Half of cycle must be multiple of precision 1{}",
+                self.precision.name()
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn timeunit(&self) -> String {
+        format!("1{}", self.unit.name())
+    }
+
+    pub fn timeprecision(&self) -> String {
+        format!("1{}", self.precision.name())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{TimeScale, TimeUnit};
+
+    #[test]
+    fn test_format() {
+        let mut scale = TimeScale::default();
+        assert_eq!(scale.format(0.0), "0 ns");
+        assert_eq!(scale.format(3.0), "6 ns");
+        assert_eq!(scale.format(750.0), "1.5 us");
+        assert_eq!(scale.format(0.25), "500 ps");
+        assert_eq!(scale.delay(0.5), "1");
+        assert_eq!(scale.parse("20"), Some(10.0));
+        assert_eq!(scale.parse("1.5 us"), Some(750.0));
+        assert_eq!(scale.parse("3 hours"), None);
+
+        scale.step = 5.0;
+        assert_eq!(scale.delay(0.5), "2.5");
+        assert!(scale.check().is_ok());
+        scale.precision = TimeUnit::Ns;
+        assert!(scale.check().is_err());
+        scale.step = 10.0;
+        assert!(scale.check().is_ok());
+        scale.precision = TimeUnit::Us;
+        assert!(scale.check().is_err());
+    }
+}
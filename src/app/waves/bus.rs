@@ -0,0 +1,357 @@
+//! Split of register into bit slices and merge of waves into one register.
+//! Linked slice is view of part of bus: its edits are written into bus and it
+//! follows every change of bus.
+
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use egui::{Ui, Vec2};
+use serde::{Deserialize, Serialize};
+
+use crate::{app::windows::WindowResult, hseparator};
+
+use super::{
+    cycles::CycleEdit, edit::WaveEdit, enums::is_identifier, BitValue, Wave, WaveDisplay, WaveType,
+};
+
+/// Identity of wave, unique in project and kept in project file
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct WaveId(u64);
+
+impl Default for WaveId {
+    /// Random id
+    fn default() -> Self {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let mut h = RandomState::new().build_hasher();
+        h.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+        WaveId(h.finish())
+    }
+}
+
+/// Part `[msb:lsb]` of other wave
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct BusSlice {
+    pub bus: WaveId,
+    pub msb: usize,
+    pub lsb: usize,
+}
+
+/// Request of wave to project
+pub enum WaveAction {
+    /// New waves to insert after this one
+    Split(Vec<Wave>),
+    /// Choose waves to merge with this one
+    Merge,
+    /// Choose group of this wave
+    Group,
+    /// Choose clock domain of this wave
+    Domain,
+    Move(WaveMove),
+    /// Insert or delete cycles of all waves
+    Cycles(CycleEdit),
+    /// Handle of wave is dragged
+    Drag,
+    /// Handle of wave is released
+    Drop,
+    /// New marker at cycle
+    Marker(usize),
+    /// Place cursor of index at cycle
+    Cursor(usize, usize),
+}
+
+/// Move of wave between neighbours in its group
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WaveMove {
+    Up,
+    Down,
+    Top,
+    Bottom,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SliceName {
+    pub name: String,
+    pub msb: usize,
+    pub lsb: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub(super) struct BusSplit {
+    pub size: usize,
+    pub slices: Vec<SliceName>,
+    pub linked: bool,
+}
+
+impl BusSplit {
+    /// One wire for each bit, most significant first
+    pub fn bits(name: &str, size: usize) -> Vec<SliceName> {
+        (0..size)
+            .rev()
+            .map(|i| SliceName {
+                name: format!("{}_{}", name, i),
+                msb: i,
+                lsb: i,
+            })
+            .collect()
+    }
+
+    fn check(&self) -> Result<(), String> {
+        for s in &self.slices {
+            if !is_identifier(&s.name) {
+                return Err(format!("Name `{}` isn't identifier", s.name));
+            }
+            if s.msb < s.lsb || s.msb >= self.size {
+                return Err(format!("Slice [{}:{}] out of register", s.msb, s.lsb));
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn display(&mut self, ui: &mut Ui, bus_name: &str) -> WindowResult {
+        let mut open = true;
+        let mut state = WindowResult::Open;
+        let max = self.size - 1;
+        egui::Window::new("Split bus")
+            .open(&mut open)
+            .show(ui.ctx(), |ui| {
+                let mut remove = None;
+                egui::Grid::new("bus_slices").show(ui, |ui| {
+                    for (i, s) in self.slices.iter_mut().enumerate() {
+                        ui.text_edit_singleline(&mut s.name);
+                        ui.add(egui::DragValue::new(&mut s.msb).clamp_range(0..=max));
+                        ui.add(egui::DragValue::new(&mut s.lsb).clamp_range(0..=max));
+                        if ui.button("Delete").clicked() {
+                            remove = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+                if let Some(i) = remove {
+                    self.slices.remove(i);
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("Add slice").clicked() {
+                        self.slices.push(SliceName {
+                            name: format!("{}_{}_0", bus_name, max),
+                            msb: max,
+                            lsb: 0,
+                        });
+                    }
+                    if ui.button("All bits").clicked() {
+                        self.slices = BusSplit::bits(bus_name, self.size);
+                    }
+                });
+                ui.checkbox(&mut self.linked, "Linked: edits go to bus and back");
+                hseparator!(ui);
+                let checked = self.check();
+                if let Err(e) = &checked {
+                    ui.label(e);
+                }
+                if ui
+                    .add_enabled(checked.is_ok(), egui::Button::new("Save"))
+                    .clicked()
+                {
+                    state = WindowResult::Save;
+                }
+            });
+        if !open {
+            state = WindowResult::Cancel;
+        }
+        state
+    }
+}
+
+impl Wave {
+    pub fn id(&self) -> WaveId {
+        self.id
+    }
+
+    pub fn take_action(&mut self) -> Option<WaveAction> {
+        self.action.take()
+    }
+
+    /// New wave of type fitting `size` bits
+    fn with_data(name: &str, data: Vec<BitValue>, size: usize, ui_size: Vec2) -> Wave {
+        let mut w = Wave::new(name, 0, ui_size);
+        if size == 1 {
+            w.tp = WaveType::Wire;
+        } else {
+            w.tp = WaveType::Reg(size);
+            w.display = WaveDisplay::Hex;
+        }
+        w.data = data;
+        w.refresh_min_max();
+        w
+    }
+
+    /// Waves of slices of this register
+    pub(super) fn split(&self, slices: &[SliceName], linked: bool) -> Vec<Wave> {
+        slices
+            .iter()
+            .map(|s| {
+                let data = self.data.iter().map(|v| v.slice(s.msb, s.lsb)).collect();
+                let size = s.msb - s.lsb + 1;
+                let mut w = Wave::with_data(&s.name, data, size, self.current_size);
+                if linked {
+                    w.slice = Some(BusSlice {
+                        bus: self.id,
+                        msb: s.msb,
+                        lsb: s.lsb,
+                    });
+                }
+                w
+            })
+            .collect()
+    }
+
+    /// Register of concatenated waves, first wave is most significant part
+    pub fn merge(name: &str, parts: &[&Wave]) -> Wave {
+        let size: usize = parts.iter().map(|w| w.reg_size()).sum();
+        let len = parts.iter().map(|w| w.len()).max().unwrap_or(0);
+        let data = (0..len)
+            .map(|i| {
+                let mut v = BitValue::new(size);
+                let mut lsb = size;
+                for p in parts {
+                    lsb -= p.reg_size();
+                    let part = p.data.get(i).cloned();
+                    v.set_slice(lsb, &part.unwrap_or_else(|| BitValue::new(p.reg_size())));
+                }
+                v
+            })
+            .collect();
+        let ui_size = parts.first().map_or(Vec2::ZERO, |w| w.current_size);
+        Wave::with_data(name, data, size, ui_size)
+    }
+
+    /// Link wave as part `[msb:lsb]` of `bus`. Returns edit reverting the link
+    pub fn link(&mut self, bus: WaveId, msb: usize, lsb: usize) -> WaveEdit {
+        let edit = self.content_edit();
+        self.slice = Some(BusSlice { bus, msb, lsb });
+        edit
+    }
+
+    pub(super) fn unlink(&mut self) {
+        self.record_content();
+        self.slice = None;
+    }
+}
+
+/// Write edits of linked slices into their buses, then update slices of changed buses.
+/// `force` updates all slices, e.g. after undo
+pub fn sync_buses(waves: &mut [Wave], force: bool) {
+    let find = |waves: &[Wave], id: WaveId| waves.iter().position(|w| w.id == id);
+    for s in 0..waves.len() {
+        let Some(slice) = waves[s].slice else {
+            continue;
+        };
+        let Some(b) = find(waves, slice.bus) else {
+            continue;
+        };
+        let mut values: Vec<(usize, BitValue)> = Vec::new();
+        for e in &waves[s].edits {
+            match e {
+                WaveEdit::Samples(samples) => values.extend(
+                    samples
+                        .iter()
+                        .filter_map(|(i, _)| waves[s].data.get(*i).map(|v| (*i, v.clone()))),
+                ),
+                WaveEdit::Content(_) => {}
+            }
+        }
+        if values.is_empty() {
+            continue;
+        }
+        let bus = &mut waves[b];
+        let samples = values
+            .iter()
+            .filter_map(|(i, _)| bus.data.get(*i).map(|v| (*i, v.clone())))
+            .collect();
+        bus.edits.push(WaveEdit::Samples(samples));
+        for (i, v) in values {
+            if let Some(d) = bus.data.get_mut(i) {
+                d.set_slice(slice.lsb, &v);
+            }
+        }
+        bus.refresh_min_max();
+    }
+    for s in 0..waves.len() {
+        let Some(slice) = waves[s].slice else {
+            continue;
+        };
+        let Some(b) = find(waves, slice.bus) else {
+            continue;
+        };
+        if !force && waves[b].edits.is_empty() {
+            continue;
+        }
+        let data = waves[b]
+            .data
+            .iter()
+            .map(|v| v.slice(slice.msb, slice.lsb))
+            .collect();
+        waves[s].data = data;
+        waves[s].refresh_min_max();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use egui::Vec2;
+
+    use super::{sync_buses, BusSplit};
+    use crate::app::waves::{BitValue, Wave, WaveEdit, WaveType};
+
+    fn hex(w: &Wave) -> Vec<String> {
+        w.data.iter().map(|v| v.to_hex()).collect()
+    }
+
+    #[test]
+    fn test_split_merge() {
+        let mut bus = Wave::new("ctrl", 2, Vec2::ZERO);
+        bus.set_type(WaveType::Reg(8));
+        bus.data[1] = BitValue::from_u64(8, 0xa5);
+        let slices = BusSplit::bits("ctrl", 8);
+        assert_eq!(slices[0].name, "ctrl_7");
+        let bits = bus.split(&slices, false);
+        assert_eq!(bits.len(), 8);
+        assert_eq!(bits[0].reg_size(), 1);
+        assert_eq!(hex(&bits[0]), ["0", "1"]);
+        assert_eq!(hex(&bits[1]), ["0", "0"]);
+
+        let parts: Vec<&Wave> = bits.iter().collect();
+        let merged = Wave::merge("ctrl2", &parts);
+        assert_eq!(merged.reg_size(), 8);
+        assert_eq!(hex(&merged), hex(&bus));
+    }
+
+    #[test]
+    fn test_linked() {
+        let mut bus = Wave::new("ctrl", 2, Vec2::ZERO);
+        bus.set_type(WaveType::Reg(8));
+        let slices = [super::SliceName {
+            name: "mode".into(),
+            msb: 5,
+            lsb: 4,
+        }];
+        let mode = bus.split(&slices, true).remove(0);
+        let mut waves = vec![bus, mode];
+
+        // Edit of slice goes to bus
+        waves[1].data[0] = BitValue::from_u64(2, 3);
+        waves[1]
+            .edits
+            .push(WaveEdit::Samples(vec![(0, BitValue::new(2))]));
+        sync_buses(&mut waves, false);
+        assert_eq!(hex(&waves[0]), ["30", "00"]);
+        assert_eq!(waves[0].take_edits().len(), 1);
+
+        // Edit of bus goes to slice
+        waves[0].data[1] = BitValue::from_u64(8, 0xef);
+        sync_buses(&mut waves, true);
+        assert_eq!(hex(&waves[1]), ["3", "2"]);
+    }
+}
@@ -0,0 +1,234 @@
+//! Copy, cut and paste of selected time span over several waves. Clipboard holds plain
+//! text, one sized literal like `8'hff` per cell, line per cycle and tab separated column
+//! per wave, so values can be moved to spreadsheets and back.
+
+use std::ops::Range;
+
+use egui::{Event, InputState, Ui};
+use log::warn;
+use miette::ErrReport;
+
+use super::{BitValue, Wave, WaveEdit};
+
+impl Wave {
+    /// Selected cycles, `None` for selection with gaps
+    fn selected_span(&self) -> Option<Range<usize>> {
+        match self.selected_runs().as_slice() {
+            [run] => Some(run.start.min(self.data.len())..run.end.min(self.data.len())),
+            _ => None,
+        }
+    }
+
+    /// Literals of selected span
+    fn selection_column(&self) -> Option<Vec<String>> {
+        let span = self.selected_span()?;
+        Some(self.data[span].iter().map(|v| v.to_literal()).collect())
+    }
+
+    /// Clear selected span to zero
+    fn cut(&mut self) {
+        let Some(span) = self.selected_span() else {
+            return;
+        };
+        if !self.editable() {
+            return;
+        }
+        self.edits.push(WaveEdit::Samples(
+            span.clone().map(|i| (i, self.data[i].clone())).collect(),
+        ));
+        self.data[span].iter_mut().for_each(|v| v.set_zero());
+        self.refresh_min_max();
+    }
+
+    /// Values of `cells` converted to wave width, `None` for empty cell
+    fn parse_column(&self, cells: &[&str]) -> Result<Vec<Option<BitValue>>, ErrReport> {
+        let size = self.reg_size();
+        cells
+            .iter()
+            .map(|c| match c.trim() {
+                "" => Ok(None),
+                c => {
+                    let mut v = BitValue::from_literal(c, size)?;
+                    v.set_size(size).ok();
+                    Ok(Some(v))
+                }
+            })
+            .collect()
+    }
+
+    /// Values written from cycle `at`, values over end of wave dropped
+    fn write_column(&mut self, at: usize, values: Vec<Option<BitValue>>) {
+        let end = (at + values.len()).min(self.data.len());
+        if at >= end || !self.editable() {
+            return;
+        }
+        let mut samples = Vec::new();
+        for (i, v) in (at..end).zip(values) {
+            if let Some(v) = v {
+                samples.push((i, self.data[i].clone()));
+                self.data[i].assign(v);
+            }
+        }
+        if !samples.is_empty() {
+            self.edits.push(WaveEdit::Samples(samples));
+            self.refresh_min_max();
+        }
+    }
+}
+
+/// Text of selected spans, column per wave with selection in order of waves.
+/// `None` without selection or when selection of some wave has gaps
+fn copy_text(waves: &[Wave]) -> Option<String> {
+    let mut columns = Vec::new();
+    for w in waves.iter().filter(|w| w.has_selection()) {
+        let Some(column) = w.selection_column() else {
+            warn!("Selection of {} has gaps, nothing copied", w.name);
+            return None;
+        };
+        columns.push(column);
+    }
+    let len = columns.iter().map(Vec::len).max()?;
+    let lines: Vec<String> = (0..len)
+        .map(|i| {
+            let cells: Vec<&str> = columns
+                .iter()
+                .map(|c| c.get(i).map_or("", String::as_str))
+                .collect();
+            cells.join("\t")
+        })
+        .collect();
+    Some(lines.join("\n"))
+}
+
+/// Columns of `text` written from cycle `at` into waves starting from `first`.
+/// Nothing is written when some value can't be parsed
+fn paste_text(waves: &mut [Wave], first: usize, at: usize, text: &str) -> Result<(), ErrReport> {
+    let lines: Vec<Vec<&str>> = text
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.split('\t').collect())
+        .collect();
+    let count = lines.iter().map(Vec::len).max().unwrap_or(0);
+    let targets = waves.iter().skip(first).take(count);
+    let mut columns = Vec::with_capacity(count);
+    for (k, w) in targets.enumerate() {
+        let cells: Vec<&str> = lines
+            .iter()
+            .map(|l| l.get(k).copied().unwrap_or(""))
+            .collect();
+        columns.push(w.parse_column(&cells)?);
+    }
+    for (w, values) in waves.iter_mut().skip(first).zip(columns) {
+        w.write_column(at, values);
+    }
+    Ok(())
+}
+
+/// Copy or cut selected spans of all waves, paste at wave and cycle under pointer
+pub fn handle_clipboard(waves: &mut [Wave], ui: &Ui, input: &InputState) {
+    // Text fields handle clipboard by themselves
+    if ui.memory(|m| m.focus().is_some()) {
+        return;
+    }
+    for event in &input.events {
+        match event {
+            Event::Copy | Event::Cut => {
+                let Some(text) = copy_text(waves) else {
+                    continue;
+                };
+                ui.ctx().output_mut(|o| o.copied_text = text);
+                if let Event::Cut = event {
+                    waves.iter_mut().for_each(|w| w.cut());
+                }
+            }
+            Event::Paste(text) => {
+                let Some(pos) = input.pointer.hover_pos() else {
+                    continue;
+                };
+                let target = waves.iter().enumerate().find_map(|(i, w)| {
+                    let at = w.cycle_at(pos).filter(|_| w.plot_contains(pos))?;
+                    Some((i, at))
+                });
+                if let Some((first, at)) = target {
+                    if let Err(e) = paste_text(waves, first, at, text) {
+                        warn!("Can't paste into {}: {:?}", waves[first].name, e);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use egui::Vec2;
+
+    use super::{copy_text, paste_text};
+    use crate::app::waves::{Wave, WaveType};
+
+    fn hex(w: &Wave) -> Vec<String> {
+        w.data.iter().map(|v| v.to_hex()).collect()
+    }
+
+    #[test]
+    fn test_copy_paste() {
+        let mut waves = vec![Wave::new("a", 8, Vec2::ZERO)];
+        waves[0].set_type(WaveType::Reg(8));
+        paste_text(&mut waves, 0, 2, "8'h1f\n8'bxxxx0001\n\n255\n").unwrap();
+        waves[0].selected_data.extend(2..5);
+        let text = copy_text(&waves).unwrap();
+        assert_eq!(text, "8'h1f\n8'hx1\n8'hff");
+
+        let mut b = vec![Wave::new("b", 4, Vec2::ZERO)];
+        b[0].set_type(WaveType::Reg(4));
+        paste_text(&mut b, 0, 2, &text).unwrap();
+        assert_eq!(hex(&b[0]), ["0", "0", "f", "1"]);
+        assert_eq!(b[0].take_edits().len(), 1);
+
+        waves[0].cut();
+        assert_eq!(waves[0].data[3].to_hex(), "00");
+        assert!(paste_text(&mut waves, 0, 0, "8'h1ff").is_err());
+        assert_eq!(waves[0].data[0].to_hex(), "00");
+
+        // Cycles between selected ones are neither copied nor cleared
+        paste_text(&mut waves, 0, 0, "1\n2\n3\n4\n5\n6\n7\n8").unwrap();
+        waves[0].selected_data.clear();
+        waves[0].selected_data.extend([2, 6]);
+        assert_eq!(copy_text(&waves), None);
+        waves[0].cut();
+        assert_eq!(waves[0].data[4].to_hex(), "05");
+    }
+
+    #[test]
+    fn test_copy_paste_waves() {
+        let mut waves: Vec<Wave> = ["a", "b", "c"]
+            .into_iter()
+            .map(|n| {
+                let mut w = Wave::new(n, 4, Vec2::ZERO);
+                w.set_type(WaveType::Reg(8));
+                w
+            })
+            .collect();
+        paste_text(&mut waves, 0, 0, "1\t5\n2\t6\n3\t7").unwrap();
+        assert_eq!(hex(&waves[0]), ["01", "02", "03", "00"]);
+        assert_eq!(hex(&waves[1]), ["05", "06", "07", "00"]);
+        assert_eq!(hex(&waves[2]), ["00"; 4]);
+
+        waves[0].selected_data.extend(1..3);
+        waves[1].selected_data.extend(1..3);
+        let text = copy_text(&waves).unwrap();
+        assert_eq!(text, "8'h02\t8'h06\n8'h03\t8'h07");
+
+        // Columns go to waves from pasted one, over last wave dropped
+        paste_text(&mut waves, 1, 2, &text).unwrap();
+        assert_eq!(hex(&waves[1]), ["05", "06", "02", "03"]);
+        assert_eq!(hex(&waves[2]), ["00", "00", "06", "07"]);
+        // Empty cell keeps value, invalid one stops whole paste
+        paste_text(&mut waves, 0, 0, "\t9\n4").unwrap();
+        assert_eq!(hex(&waves[0]), ["01", "04", "03", "00"]);
+        assert_eq!(hex(&waves[1])[0], "09");
+        assert!(paste_text(&mut waves, 0, 0, "7\t8'h1ff").is_err());
+        assert_eq!(hex(&waves[0])[0], "01");
+    }
+}
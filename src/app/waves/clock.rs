@@ -0,0 +1,156 @@
+//! Generated clocks: enable by other wave and signals of exported clock.
+
+use std::rc::Rc;
+
+use egui::Ui;
+
+use super::{domain::update_domains, value::Logic, wtype::Clock, Wave, WaveId, WaveType};
+
+impl Wave {
+    /// Signal of clock in testbench, positive one of differential pair
+    pub fn clock_signal(&self) -> String {
+        match self.tp {
+            WaveType::Clock(c) if c.differential => self.name() + "_p",
+            _ => self.name(),
+        }
+    }
+
+    /// Negative signal of differential clock
+    pub fn clock_negative(&self) -> Option<String> {
+        match self.tp {
+            WaveType::Clock(c) if c.differential => Some(self.name() + "_n"),
+            _ => None,
+        }
+    }
+
+    /// Wave enabling clock
+    pub fn clock_gate(&self) -> Option<WaveId> {
+        match self.tp {
+            WaveType::Clock(c) => c.gate,
+            _ => None,
+        }
+    }
+
+    /// Verilog expression of clock level, see [`Clock::export_level`]
+    pub fn export_clock_level(&self, cycle: &str, gate: Option<&str>) -> Option<String> {
+        match self.tp {
+            WaveType::Clock(c) => Some(c.export_level(cycle, gate)),
+            _ => None,
+        }
+    }
+
+    /// Name of wave enabling clock, `None` for missing wave
+    pub(super) fn gate_name(&self, gate: WaveId) -> Option<&str> {
+        let choice = self.gate_choices.iter().find(|(id, _)| *id == gate);
+        choice.map(|(_, name)| name.as_str())
+    }
+
+    /// Start, stop, enable and pair of clock under its type
+    pub(super) fn draw_clock_info(&self, ui: &mut Ui, c: Clock) {
+        let time = |v: usize| self.scale.format(v as f64);
+        let mut parts = Vec::new();
+        if c.start > 0 {
+            parts.push(format!("start->{}", time(c.start)));
+        }
+        if let Some(stop) = c.stop {
+            parts.push(format!("stop->{}", time(stop)));
+        }
+        if let Some(n) = self.clock_negative() {
+            parts.push(format!("pair {}/{}", self.clock_signal(), n));
+        }
+        if !parts.is_empty() {
+            ui.label(parts.join(", "));
+        }
+        match c.gate.map(|g| self.gate_name(g)) {
+            Some(Some(name)) => {
+                ui.label(format!("Enabled by {}", name));
+            }
+            Some(None) => {
+                ui.colored_label(egui::Color32::RED, "Enable wave of clock is missing");
+            }
+            None => {}
+        }
+    }
+
+    /// Recompute clock enabled in cycles of `enable`
+    fn apply_gate(&mut self, enable: &[bool]) {
+        let WaveType::Clock(c) = self.tp else {
+            return;
+        };
+        for (i, v) in self.data.iter_mut().enumerate() {
+            let on = enable.get(i).copied().unwrap_or(false);
+            v.set_bool(c.level(i) && on);
+        }
+    }
+}
+
+/// Regate clocks by current samples of their enables, then refresh clock
+/// domains shown on waves
+pub fn update_clocks(waves: &mut [Wave]) {
+    let choices: Vec<(WaveId, String)> = waves
+        .iter()
+        .filter(|w| !w.is_clock())
+        .map(|w| (w.id(), w.name()))
+        .collect();
+    let choices = Rc::new(choices);
+    for i in 0..waves.len() {
+        waves[i].gate_choices = choices.clone();
+        let WaveType::Clock(c) = waves[i].tp else {
+            continue;
+        };
+        let gate = c.gate.and_then(|g| waves.iter().find(|w| w.id() == g));
+        match gate.filter(|g| !g.is_clock()) {
+            Some(g) => {
+                let enable: Vec<bool> = g.data.iter().map(|v| v.get_bit(0) == Logic::V1).collect();
+                waves[i].apply_gate(&enable);
+            }
+            None => waves[i].recalculate_clock(),
+        }
+    }
+    update_domains(waves);
+}
+
+#[cfg(test)]
+mod test {
+    use egui::Vec2;
+
+    use super::update_clocks;
+    use crate::app::waves::{wtype::Clock, BitValue, Wave, WaveType};
+
+    fn bits(w: &Wave) -> String {
+        w.data.iter().map(|v| v.to_bin()).collect()
+    }
+
+    #[test]
+    fn test_clock() {
+        let mut waves = vec![
+            Wave::new("clk", 12, Vec2::ZERO),
+            Wave::new("en", 12, Vec2::ZERO),
+        ];
+        let mut clock = Clock::new();
+        clock.start = 2;
+        clock.stop = Some(10);
+        clock.phase = 1;
+        waves[0].set_type(WaveType::Clock(clock));
+        assert_eq!(bits(&waves[0]), "000101010100");
+
+        for v in &mut waves[1].data[5..] {
+            *v = BitValue::from_u64(1, 1);
+        }
+        clock.gate = Some(waves[1].id());
+        clock.differential = true;
+        waves[0].set_type(WaveType::Clock(clock));
+        update_clocks(&mut waves);
+        assert_eq!(bits(&waves[0]), "000001010100");
+        assert_eq!(waves[0].clock_signal(), "clk_p");
+        assert_eq!(waves[0].clock_negative().as_deref(), Some("clk_n"));
+
+        let level = clock.export_level("clk_cycle", Some("en_data[clk_cycle]"));
+        assert_eq!(
+            level,
+            "clk_cycle >= 2 && clk_cycle < 10 && (clk_cycle - 2 + 1) % 2 < 1 \
+             && en_data[clk_cycle] === 1'b1"
+        );
+        assert_eq!(Clock::new().export_level("c", None), "(c) % 2 < 1");
+    }
+}
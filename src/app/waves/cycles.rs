@@ -0,0 +1,168 @@
+//! Insert and delete of cycles inside of wave. Wave edited alone keeps its length,
+//! edit of all waves changes time size of project.
+
+use std::ops::Range;
+
+use egui::Ui;
+use serde::{Deserialize, Serialize};
+
+use crate::{app::windows::WindowResult, hseparator};
+
+use super::{BitValue, Wave};
+
+/// Change of time axis
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub enum CycleEdit {
+    /// `count` cycles before cycle `at`, holding value of previous cycle or zero
+    Insert { at: usize, count: usize },
+    /// Sorted not overlapping ranges of cycles
+    Delete(Vec<Range<usize>>),
+}
+
+impl CycleEdit {
+    /// Length of wave of `len` cycles after edit
+    pub fn new_len(&self, len: usize) -> usize {
+        match self {
+            CycleEdit::Insert { count, .. } => len + count,
+            CycleEdit::Delete(ranges) => {
+                let deleted: usize = ranges
+                    .iter()
+                    .map(|r| r.end.min(len).saturating_sub(r.start))
+                    .sum();
+                len - deleted.min(len)
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub(super) struct CycleInsert {
+    pub at: usize,
+    pub count: usize,
+    pub after: bool,
+    /// Insert into all waves of project
+    pub all: bool,
+    pub max: usize,
+}
+
+impl CycleInsert {
+    pub fn edit(&self) -> CycleEdit {
+        CycleEdit::Insert {
+            at: self.at + self.after as usize,
+            count: self.count,
+        }
+    }
+
+    pub(crate) fn display(&mut self, ui: &mut Ui) -> WindowResult {
+        let mut open = true;
+        let mut state = WindowResult::Open;
+        egui::Window::new("Insert cycles")
+            .open(&mut open)
+            .show(ui.ctx(), |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Count");
+                    ui.add(egui::DragValue::new(&mut self.count).clamp_range(1..=usize::MAX));
+                });
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut self.after, false, "Before");
+                    ui.selectable_value(&mut self.after, true, "After");
+                    ui.label("cycle");
+                    ui.add(egui::DragValue::new(&mut self.at).clamp_range(0..=self.max));
+                });
+                ui.checkbox(&mut self.all, "All waves, time size grows");
+                hseparator!(ui);
+                if ui.button("Save").clicked() {
+                    state = WindowResult::Save;
+                }
+            });
+        if !open {
+            state = WindowResult::Cancel;
+        }
+        state
+    }
+}
+
+impl Wave {
+    /// Insert or delete cycles. Wave keeping length is cut at end after insert and
+    /// extended by last value after delete
+    pub fn edit_cycles(&mut self, edit: &CycleEdit, keep_len: bool) {
+        let len = self.data.len();
+        match edit {
+            CycleEdit::Insert { at, count } => {
+                let at = (*at).min(len);
+                let hold = at
+                    .checked_sub(1)
+                    .and_then(|i| self.data.get(i).cloned())
+                    .unwrap_or_else(|| BitValue::new(self.reg_size()));
+                self.data
+                    .splice(at..at, std::iter::repeat(hold).take(*count));
+            }
+            CycleEdit::Delete(ranges) => {
+                for r in ranges.iter().rev() {
+                    let r = r.start.min(len)..r.end.min(len);
+                    self.data.drain(r);
+                }
+            }
+        }
+        if keep_len {
+            self.fit_len(len);
+        }
+        self.selected_data.clear();
+        self.recalculate_clock();
+        self.refresh_min_max();
+    }
+
+    /// Cycles of linked slice or derived wave follow its source
+    pub(super) fn owns_cycles(&self) -> bool {
+        self.editable() && self.slice.is_none()
+    }
+
+    /// Delete of selected cycles
+    pub(super) fn delete_selected(&self) -> CycleEdit {
+        CycleEdit::Delete(self.selected_runs())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use egui::Vec2;
+
+    use super::CycleEdit;
+    use crate::app::waves::{wtype::Clock, BitValue, Wave, WaveType};
+
+    fn hex(w: &Wave) -> String {
+        w.data.iter().map(|v| v.to_hex()).collect()
+    }
+
+    #[test]
+    fn test_cycles() {
+        let mut w = Wave::new("a", 6, Vec2::ZERO);
+        w.set_type(WaveType::Reg(4));
+        for i in 0..6 {
+            w.data[i] = BitValue::from_u64(4, i as u64 + 1);
+        }
+        let mut one = Wave::new("b", 0, Vec2::ZERO);
+        one.data = w.data.clone();
+
+        w.edit_cycles(&CycleEdit::Insert { at: 2, count: 2 }, false);
+        assert_eq!(hex(&w), "12223456");
+        one.edit_cycles(&CycleEdit::Insert { at: 0, count: 1 }, true);
+        assert_eq!(hex(&one), "012345");
+
+        w.select(1..=3);
+        w.selected_data.insert(6);
+        let edit = w.delete_selected();
+        assert_eq!(edit, CycleEdit::Delete(vec![1..4, 6..7]));
+        assert_eq!(edit.new_len(8), 4);
+        assert_eq!(edit.new_len(2), 1);
+        w.edit_cycles(&edit, true);
+        assert_eq!(hex(&w), "13466666");
+        assert!(!w.has_selection());
+
+        let mut clk = Wave::new("clk", 4, Vec2::ZERO);
+        clk.set_type(WaveType::Clock(Clock::new()));
+        let before = hex(&clk);
+        clk.edit_cycles(&CycleEdit::Insert { at: 1, count: 4 }, false);
+        assert_eq!(hex(&clk), before.repeat(2));
+    }
+}
@@ -0,0 +1,217 @@
+//! Clock domains: wave of domain is sampled on rising edges of clock wave
+//! instead of every cycle of testbench clock.
+
+use egui::{
+    plot::{Line, PlotPoints, PlotUi},
+    Color32,
+};
+
+use super::{edit::WaveEdit, value::Logic, BitValue, Wave, WaveId, WaveType};
+
+/// Colors of domains in order of clock waves
+const COLORS: [Color32; 6] = [
+    Color32::from_rgb(60, 160, 220),
+    Color32::from_rgb(220, 120, 40),
+    Color32::from_rgb(90, 190, 90),
+    Color32::from_rgb(200, 90, 200),
+    Color32::from_rgb(210, 190, 50),
+    Color32::from_rgb(90, 200, 190),
+];
+
+/// Clock of domain as shown on wave
+#[derive(Clone)]
+pub(super) struct DomainClock {
+    pub name: String,
+    pub color: Color32,
+    pub edges: Vec<usize>,
+}
+
+impl DomainClock {
+    /// Ticks at bottom of plot on cycles sampled by clock
+    pub fn draw(&self, plot_ui: &mut PlotUi, min: f64, max: f64) {
+        let bounds = plot_ui.plot_bounds();
+        let (first, last) = (bounds.min()[0].floor(), bounds.max()[0].ceil());
+        let height = (max - min) / 8.0;
+        let shown: Vec<f64> = self
+            .edges
+            .iter()
+            .map(|e| *e as f64)
+            .filter(|e| (first..=last).contains(e))
+            .collect();
+        // Ticks of too many edges merge into a bar
+        if shown.len() > 1000 {
+            return;
+        }
+        for e in shown {
+            let tick = PlotPoints::new(vec![[e, min], [e, min + height]]);
+            plot_ui.line(Line::new(tick).color(self.color).name(""));
+        }
+    }
+}
+
+/// Waves sampled by one clock in exported testbench
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Domain {
+    /// Index of clock wave, `None` is clock of testbench
+    pub clock: Option<usize>,
+    pub waves: Vec<usize>,
+}
+
+impl Wave {
+    pub fn is_clock(&self) -> bool {
+        matches!(self.tp, WaveType::Clock(_))
+    }
+
+    /// Clock wave of domain, clock waves are always in testbench clock domain
+    pub fn domain(&self) -> Option<WaveId> {
+        self.domain.filter(|_| !self.is_clock())
+    }
+
+    /// Move wave to domain of other clock. Returns edit reverting the change
+    pub fn set_domain(&mut self, domain: Option<WaveId>) -> WaveEdit {
+        let edit = self.content_edit();
+        self.domain = domain;
+        edit
+    }
+
+    /// Cycles where bit 0 rises in verilog sense. Exported clock starts at
+    /// level of cycle 0, so cycle 0 never rises
+    pub fn rising_edges(&self) -> Vec<usize> {
+        let Some(first) = self.data.first() else {
+            return Vec::new();
+        };
+        let mut last = first.get_bit(0);
+        let mut edges = Vec::new();
+        for (i, v) in self.data.iter().enumerate() {
+            let bit = v.get_bit(0);
+            if bit != last && (last == Logic::V0 || bit == Logic::V1) {
+                edges.push(i);
+            }
+            last = bit;
+        }
+        edges
+    }
+
+    /// Samples taken by domain clock, or all samples without clock
+    pub fn domain_samples(&self, clock: Option<&Wave>) -> Vec<BitValue> {
+        match clock {
+            Some(c) => c
+                .rising_edges()
+                .into_iter()
+                .filter_map(|i| self.data.get(i).cloned())
+                .collect(),
+            None => self.data.clone(),
+        }
+    }
+}
+
+/// Refresh names and edges of domain clocks shown on waves
+pub fn update_domains(waves: &mut [Wave]) {
+    let clocks: Vec<(WaveId, DomainClock)> = waves
+        .iter()
+        .filter(|w| w.is_clock())
+        .enumerate()
+        .map(|(i, w)| {
+            let clock = DomainClock {
+                name: w.name(),
+                color: COLORS[i % COLORS.len()],
+                edges: w.rising_edges(),
+            };
+            (w.id(), clock)
+        })
+        .collect();
+    for w in waves.iter_mut() {
+        w.domain_clock = w.domain().and_then(|d| {
+            let clock = clocks.iter().find(|(id, _)| *id == d);
+            clock.map(|(_, c)| c.clone())
+        });
+    }
+}
+
+/// Domains of exported `waves`, testbench clock goes first and other clocks
+/// in order of waves. Clock of domain must be exported too
+pub fn clock_domains(waves: &[&Wave]) -> Result<Vec<Domain>, String> {
+    let mut domains = vec![Domain {
+        clock: None,
+        waves: Vec::new(),
+    }];
+    for (i, w) in waves.iter().enumerate() {
+        let clock = match w.domain() {
+            Some(id) => {
+                let c = waves.iter().position(|c| c.id() == id && c.is_clock());
+                let c = c.ok_or_else(|| format!("Clock of {} is not exported", w.name()))?;
+                Some(c)
+            }
+            None => None,
+        };
+        match domains.iter_mut().find(|d| d.clock == clock) {
+            Some(d) => d.waves.push(i),
+            None => domains.push(Domain {
+                clock,
+                waves: vec![i],
+            }),
+        }
+    }
+    Ok(domains)
+}
+
+#[cfg(test)]
+mod test {
+    use egui::Vec2;
+
+    use super::{clock_domains, update_domains, Domain};
+    use crate::app::waves::{wtype::Clock, BitValue, Wave, WaveType};
+
+    #[test]
+    fn test_domains() {
+        let mut waves = vec![
+            Wave::new("clk", 8, Vec2::ZERO),
+            Wave::new("a", 8, Vec2::ZERO),
+            Wave::new("b", 8, Vec2::ZERO),
+        ];
+        let clock = Clock {
+            period: 4,
+            duty: 2,
+            phase: 2,
+            ..Clock::new()
+        };
+        waves[0].set_type(WaveType::Clock(clock));
+        for (i, v) in waves[1].data.iter_mut().enumerate() {
+            *v = BitValue::from_u64(1, i as u64 % 2);
+        }
+        let edges = waves[0].rising_edges();
+        assert_eq!(edges, vec![2, 6]);
+        // Starts high, cycle 0 is not rising
+        let mut high = Wave::new("high", 8, Vec2::ZERO);
+        high.set_type(WaveType::Clock(Clock::new()));
+        assert_eq!(high.rising_edges(), vec![2, 4, 6]);
+
+        let clk = waves[0].id();
+        let _ = waves[1].set_domain(Some(clk));
+        update_domains(&mut waves);
+        let shown = waves[1].domain_clock.as_ref().map(|c| c.name.as_str());
+        assert_eq!(shown, Some("clk"));
+        assert!(waves[2].domain_clock.is_none());
+
+        let samples = waves[1].domain_samples(Some(&waves[0]));
+        assert_eq!(samples, vec![BitValue::from_u64(1, 0); 2]);
+        assert_eq!(waves[2].domain_samples(None).len(), 8);
+
+        let all: Vec<&Wave> = waves.iter().collect();
+        let domains = clock_domains(&all).unwrap();
+        assert_eq!(
+            domains,
+            vec![
+                Domain {
+                    clock: None,
+                    waves: vec![0, 2],
+                },
+                Domain {
+                    clock: Some(0),
+                    waves: vec![1],
+                },
+            ]
+        );
+        assert!(clock_domains(&all[1..]).is_err());
+    }
+}
@@ -0,0 +1,81 @@
+//! Reversible edits of wave for undo history. Edit keeps other state of wave,
+//! applying it exchanges this state with current one, so second apply reverts the first.
+
+use super::{bus::BusSlice, BitValue, EnumTable, FixedPoint, Wave, WaveDisplay, WaveId, WaveType};
+
+/// Type, format, link, expression, group, clock domain and samples of wave
+pub struct WaveContent {
+    tp: WaveType,
+    slice: Option<BusSlice>,
+    expr: Option<String>,
+    group: Option<WaveId>,
+    domain: Option<WaveId>,
+    display: WaveDisplay,
+    fixed: Option<FixedPoint>,
+    enum_table: Option<EnumTable>,
+    data: Vec<BitValue>,
+}
+
+pub enum WaveEdit {
+    /// Other values of some samples
+    Samples(Vec<(usize, BitValue)>),
+    /// Other content of whole wave, for edits changing type or length
+    Content(Box<WaveContent>),
+}
+
+impl Wave {
+    /// Record whole current content before it is changed by user
+    pub(super) fn record_content(&mut self) {
+        let edit = WaveEdit::Content(Box::new(self.content()));
+        self.edits.push(edit);
+    }
+
+    /// Edit for undo of resize or other change of whole wave
+    pub fn content_edit(&self) -> WaveEdit {
+        WaveEdit::Content(Box::new(self.content()))
+    }
+
+    fn content(&self) -> WaveContent {
+        WaveContent {
+            tp: self.tp,
+            slice: self.slice,
+            expr: self.expr.clone(),
+            group: self.group,
+            domain: self.domain,
+            display: self.display,
+            fixed: self.fixed,
+            enum_table: self.enum_table.clone(),
+            data: self.data.clone(),
+        }
+    }
+
+    /// Edits done by user since last call
+    pub fn take_edits(&mut self) -> Vec<WaveEdit> {
+        std::mem::take(&mut self.edits)
+    }
+
+    /// Exchange state of wave with state kept in `edit`
+    pub fn swap_edit(&mut self, edit: &mut WaveEdit) {
+        match edit {
+            WaveEdit::Samples(samples) => {
+                for (i, v) in samples.iter_mut() {
+                    if let Some(d) = self.data.get_mut(*i) {
+                        std::mem::swap(d, v);
+                    }
+                }
+            }
+            WaveEdit::Content(c) => {
+                std::mem::swap(&mut self.tp, &mut c.tp);
+                std::mem::swap(&mut self.slice, &mut c.slice);
+                std::mem::swap(&mut self.expr, &mut c.expr);
+                std::mem::swap(&mut self.group, &mut c.group);
+                std::mem::swap(&mut self.domain, &mut c.domain);
+                std::mem::swap(&mut self.display, &mut c.display);
+                std::mem::swap(&mut self.fixed, &mut c.fixed);
+                std::mem::swap(&mut self.enum_table, &mut c.enum_table);
+                std::mem::swap(&mut self.data, &mut c.data);
+            }
+        }
+        self.refresh_min_max();
+    }
+}
@@ -0,0 +1,227 @@
+use std::collections::HashSet;
+
+use egui::Ui;
+use serde::{Deserialize, Serialize};
+
+use crate::{app::windows::WindowResult, hseparator};
+
+use super::value::BitValue;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct EnumState {
+    pub value: u64,
+    pub name: String,
+}
+
+/// Symbolic names of register values, e.g. states of FSM
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct EnumTable {
+    /// SystemVerilog type name for `typedef enum`
+    pub type_name: String,
+    pub states: Vec<EnumState>,
+}
+
+impl EnumTable {
+    /// Widest register with states table, state values are `u64`
+    pub const MAX_BITS: usize = 64;
+
+    pub fn new(wave_name: &str) -> Self {
+        Self {
+            type_name: format!("{}_t", wave_name),
+            states: Vec::new(),
+        }
+    }
+
+    /// Name of value. `None` for value out of table or with `x`/`z` bits
+    pub fn name(&self, v: &BitValue) -> Option<&str> {
+        let v = v.to_u128()?;
+        self.states
+            .iter()
+            .find(|s| s.value as u128 == v)
+            .map(|s| s.name.as_str())
+    }
+
+    /// Name of value or hex of value out of table
+    pub fn print(&self, v: &BitValue) -> String {
+        match self.name(v) {
+            Some(n) => n.into(),
+            None => format!("'h{}", v.to_hex()),
+        }
+    }
+
+    /// Names must be unique identifiers, values must be unique and fit in register of `size`
+    pub fn check(&self, size: usize) -> Result<(), String> {
+        if size > Self::MAX_BITS {
+            return Err(format!(
+                "States table is for registers up to {} bits",
+                Self::MAX_BITS
+            ));
+        }
+        if !is_identifier(&self.type_name) {
+            return Err(format!("Type name `{}` isn't identifier", self.type_name));
+        }
+        let mut names = HashSet::new();
+        let mut values = HashSet::new();
+        for s in &self.states {
+            if !is_identifier(&s.name) {
+                return Err(format!("State name `{}` isn't identifier", s.name));
+            }
+            if !names.insert(s.name.as_str()) {
+                return Err(format!("State name `{}` used twice", s.name));
+            }
+            if !values.insert(s.value) {
+                return Err(format!("State value {} used twice", s.value));
+            }
+            if size < Self::MAX_BITS && s.value >> size != 0 {
+                return Err(format!(
+                    "State value {} don't fit in {} bits",
+                    s.value, size
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// SystemVerilog `typedef enum` for register of `size`
+    pub fn export(&self, size: usize) -> String {
+        let states: Vec<String> = self
+            .states
+            .iter()
+            .map(|s| format!("        {} = {}'d{}", s.name, size, s.value))
+            .collect();
+        format!(
+            "typedef enum logic [{}:0] {{\n{}\n    }} {};",
+            size.saturating_sub(1),
+            states.join(",\n"),
+            self.type_name
+        )
+    }
+}
+
+/// Verilog simple identifier
+pub(super) fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub(super) struct EnumEdit {
+    pub size: usize,
+    pub table: EnumTable,
+}
+
+impl EnumEdit {
+    pub(crate) fn display(&mut self, ui: &mut Ui) -> WindowResult {
+        let mut open = true;
+        let mut state = WindowResult::Open;
+        let max = if self.size < EnumTable::MAX_BITS {
+            (1u64 << self.size) - 1
+        } else {
+            u64::MAX
+        };
+        egui::Window::new("States")
+            .open(&mut open)
+            .show(ui.ctx(), |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Type name");
+                    ui.text_edit_singleline(&mut self.table.type_name);
+                });
+                let mut remove = None;
+                egui::Grid::new("enum_states").show(ui, |ui| {
+                    for (i, s) in self.table.states.iter_mut().enumerate() {
+                        ui.add(egui::DragValue::new(&mut s.value).clamp_range(0..=max));
+                        ui.text_edit_singleline(&mut s.name);
+                        if ui.button("Delete").clicked() {
+                            remove = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+                if let Some(i) = remove {
+                    self.table.states.remove(i);
+                }
+                if ui.button("Add state").clicked() {
+                    let value = self
+                        .table
+                        .states
+                        .iter()
+                        .map(|s| s.value.saturating_add(1))
+                        .max();
+                    self.table.states.push(EnumState {
+                        value: value.unwrap_or(0).min(max),
+                        name: format!("S{}", self.table.states.len()),
+                    });
+                }
+                hseparator!(ui);
+                let checked = self.table.check(self.size);
+                if let Err(e) = &checked {
+                    ui.label(e);
+                }
+                ui.label(egui::RichText::new("Table without states removes enum display").small());
+                if ui
+                    .add_enabled(checked.is_ok(), egui::Button::new("Save"))
+                    .clicked()
+                {
+                    state = WindowResult::Save;
+                }
+            });
+        if !open {
+            state = WindowResult::Cancel;
+        }
+        state
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{EnumState, EnumTable};
+    use crate::app::waves::BitValue;
+
+    fn table() -> EnumTable {
+        let mut t = EnumTable::new("fsm");
+        for (value, name) in [(0, "IDLE"), (1, "FETCH"), (3, "EXEC")] {
+            t.states.push(EnumState {
+                value,
+                name: name.into(),
+            });
+        }
+        t
+    }
+
+    #[test]
+    fn test_names() {
+        let t = table();
+        assert_eq!(t.print(&BitValue::from_u64(2, 1)), "FETCH");
+        assert_eq!(t.print(&BitValue::from_u64(2, 2)), "'h2");
+        let mut x = BitValue::new(2);
+        x.set_unknown();
+        assert_eq!(t.name(&x), None);
+    }
+
+    #[test]
+    fn test_check() {
+        let mut t = table();
+        assert!(t.check(2).is_ok());
+        assert!(t.check(1).is_err());
+        t.states[2].name = "FETCH".into();
+        assert!(t.check(2).is_err());
+        t.states[2].name = "2EXEC".into();
+        assert!(t.check(2).is_err());
+        t.states[2].name = "EXEC".into();
+        t.states[2].value = 0;
+        assert!(t.check(2).is_err());
+        t.states[2].value = 3;
+        assert!(t.check(64).is_ok());
+        assert!(t.check(65).is_err());
+    }
+
+    #[test]
+    fn test_export() {
+        assert_eq!(
+            table().export(2),
+            "typedef enum logic [1:0] {\n        IDLE = 2'd0,\n        FETCH = 2'd1,\n        \
+            EXEC = 2'd3\n    } fsm_t;"
+        );
+    }
+}
@@ -0,0 +1,689 @@
+//! Expressions over other waves in verilog syntax: `valid & ~stall`, `addr + 4`, `data[15:8]`,
+//! `{hi, lo}`, `sel ? a : b`. Operands are wave names and verilog literals, unsized literal
+//! is 32 bits. Result of binary operator has width of the widest operand, as [`BitValue`]
+//! operators do, shift keeps width of left operand. Compare and logic operators give one bit.
+
+use std::cmp::Ordering;
+
+use egui::Ui;
+use miette::{ErrReport, LabeledSpan, MietteDiagnostic, NarratableReportHandler};
+use serde::{Deserialize, Serialize};
+
+use crate::{app::windows::WindowResult, hseparator};
+
+use super::{BitValue, Wave, WaveDisplay, WaveType};
+
+/// Width of unsized literal
+const INTEGER: usize = 32;
+
+/// Byte offset and length in source
+type Span = (usize, usize);
+
+/// Span in source and label
+struct ExprError {
+    span: Span,
+    label: String,
+}
+
+impl ExprError {
+    fn new<S: Into<String>>(span: Span, label: S) -> Self {
+        Self {
+            span,
+            label: label.into(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Unary {
+    Not,
+    LogicNot,
+    Neg,
+    Plus,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Binary {
+    Mul,
+    Add,
+    Sub,
+    Shl,
+    Shr,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+    And,
+    Xor,
+    Or,
+    LogicAnd,
+    LogicOr,
+}
+
+impl Binary {
+    /// Operator and its precedence, higher binds tighter
+    fn from_token(t: &str) -> Option<(Self, u8)> {
+        Some(match t {
+            "*" => (Binary::Mul, 10),
+            "+" => (Binary::Add, 9),
+            "-" => (Binary::Sub, 9),
+            "<<" => (Binary::Shl, 8),
+            ">>" => (Binary::Shr, 8),
+            "<" => (Binary::Lt, 7),
+            "<=" => (Binary::Le, 7),
+            ">" => (Binary::Gt, 7),
+            ">=" => (Binary::Ge, 7),
+            "==" => (Binary::Eq, 6),
+            "!=" => (Binary::Ne, 6),
+            "&" => (Binary::And, 5),
+            "^" => (Binary::Xor, 4),
+            "|" => (Binary::Or, 3),
+            "&&" => (Binary::LogicAnd, 2),
+            "||" => (Binary::LogicOr, 1),
+            _ => return None,
+        })
+    }
+}
+
+enum Node {
+    /// Index of wave in [`Expr::inputs`]
+    Wave(usize),
+    Value(BitValue),
+    Unary(Unary, Box<Node>),
+    Binary(Binary, Box<Node>, Box<Node>),
+    Select(Box<Node>, usize, usize),
+    Cond(Box<Node>, Box<Node>, Box<Node>),
+    Concat(Vec<Node>),
+}
+
+#[derive(PartialEq)]
+enum Token {
+    Ident(String),
+    Number(BitValue),
+    Op(&'static str),
+}
+
+const OPERATORS: [&str; 27] = [
+    "<<", ">>", "<=", ">=", "==", "!=", "&&", "||", "*", "+", "-", "<", ">", "&", "^", "|", "~",
+    "!", "?", ":", "(", ")", "[", "]", "{", "}", ",",
+];
+
+/// Parsed expression
+pub struct Expr {
+    /// Names of waves and their spans in source
+    inputs: Vec<(String, Span)>,
+    root: Node,
+}
+
+impl Expr {
+    pub fn parse(s: &str) -> Result<Self, ErrReport> {
+        Self::parse_inner(s).map_err(|e| report(s, e))
+    }
+
+    fn parse_inner(s: &str) -> Result<Self, ExprError> {
+        let tokens = tokenize(s)?;
+        let mut p = Parser {
+            tokens,
+            pos: 0,
+            end: s.len(),
+            inputs: Vec::new(),
+        };
+        let root = p.expr(0)?;
+        if let Some((_, span)) = p.tokens.get(p.pos) {
+            return Err(ExprError::new(*span, "expected operator"));
+        }
+        Ok(Self {
+            inputs: p.inputs,
+            root,
+        })
+    }
+
+    /// Names of waves used in expression
+    pub fn inputs(&self) -> impl Iterator<Item = &str> {
+        self.inputs.iter().map(|(n, _)| n.as_str())
+    }
+
+    /// All inputs must be accepted by `known`
+    pub fn check<F: Fn(&str) -> bool>(&self, source: &str, known: F) -> Result<(), ErrReport> {
+        match self.inputs.iter().find(|(n, _)| !known(n)) {
+            Some((n, span)) => Err(report(
+                source,
+                ExprError::new(*span, format!("no wave `{}`", n)),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Value for input values in order of [`Expr::inputs`]
+    pub fn eval(&self, inputs: &[BitValue]) -> BitValue {
+        eval(&self.root, inputs)
+    }
+}
+
+fn report(source: &str, e: ExprError) -> ErrReport {
+    ErrReport::new(
+        MietteDiagnostic::new("Error in expression")
+            .with_code(source)
+            .with_severity(miette::Severity::Error)
+            .with_help(
+                "Expression uses wave names, verilog literals, operators \
+                ~ ! - * + << >> < <= > >= == != & ^ | && || ?: , part select [msb:lsb] \
+                and concatenation {a, b}",
+            )
+            .with_label(LabeledSpan::at(e.span, e.label)),
+    )
+}
+
+fn tokenize(s: &str) -> Result<Vec<(Token, Span)>, ExprError> {
+    let mut tokens = Vec::new();
+    let mut chars = s.char_indices().peekable();
+    while let Some(&(at, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let word = |chars: &mut std::iter::Peekable<std::str::CharIndices<'_>>| {
+            let mut end = at;
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_ascii_alphanumeric() || c == '_' || c == '$' || c == '\'' || c == '?') {
+                    break;
+                }
+                // `?` is digit only inside of literal
+                if c == '?' && !s[at..i].contains('\'') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            end
+        };
+        if c.is_ascii_alphabetic() || c == '_' {
+            let end = word(&mut chars);
+            tokens.push((Token::Ident(s[at..end].into()), (at, end - at)));
+        } else if c.is_ascii_digit() || c == '\'' {
+            let end = word(&mut chars);
+            let text = &s[at..end];
+            let v = BitValue::from_literal(text, INTEGER)
+                .map_err(|_| ExprError::new((at, end - at), "wrong number"))?;
+            tokens.push((Token::Number(v), (at, end - at)));
+        } else {
+            let rest = &s[at..];
+            let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(**op)) else {
+                return Err(ExprError::new((at, c.len_utf8()), "unknown symbol"));
+            };
+            for _ in 0..op.len() {
+                chars.next();
+            }
+            tokens.push((Token::Op(op), (at, op.len())));
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(Token, Span)>,
+    pos: usize,
+    /// Length of source for errors at end
+    end: usize,
+    inputs: Vec<(String, Span)>,
+}
+
+impl Parser {
+    fn peek_op(&self) -> Option<&'static str> {
+        match self.tokens.get(self.pos) {
+            Some((Token::Op(op), _)) => Some(op),
+            _ => None,
+        }
+    }
+
+    fn span(&self) -> Span {
+        self.tokens
+            .get(self.pos)
+            .map_or((self.end, 0), |(_, span)| *span)
+    }
+
+    fn expect(&mut self, op: &str) -> Result<(), ExprError> {
+        if self.peek_op() == Some(op) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(ExprError::new(self.span(), format!("expected `{}`", op)))
+        }
+    }
+
+    /// Binary operators from precedence `min`, then condition
+    fn expr(&mut self, min: u8) -> Result<Node, ExprError> {
+        let mut left = self.unary()?;
+        while let Some((op, prec)) = self.peek_op().and_then(Binary::from_token) {
+            if prec < min {
+                break;
+            }
+            self.pos += 1;
+            let right = self.expr(prec + 1)?;
+            left = Node::Binary(op, Box::new(left), Box::new(right));
+        }
+        if min == 0 && self.peek_op() == Some("?") {
+            self.pos += 1;
+            let a = self.expr(0)?;
+            self.expect(":")?;
+            let b = self.expr(0)?;
+            left = Node::Cond(Box::new(left), Box::new(a), Box::new(b));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Node, ExprError> {
+        let op = match self.peek_op() {
+            Some("~") => Unary::Not,
+            Some("!") => Unary::LogicNot,
+            Some("-") => Unary::Neg,
+            Some("+") => Unary::Plus,
+            _ => return self.primary(),
+        };
+        self.pos += 1;
+        Ok(Node::Unary(op, Box::new(self.unary()?)))
+    }
+
+    fn primary(&mut self) -> Result<Node, ExprError> {
+        let span = self.span();
+        self.pos += 1;
+        let node = match self.tokens.get(self.pos - 1).map(|(t, _)| t) {
+            Some(Token::Ident(name)) => {
+                let index = match self.inputs.iter().position(|(n, _)| n == name) {
+                    Some(i) => i,
+                    None => {
+                        self.inputs.push((name.clone(), span));
+                        self.inputs.len() - 1
+                    }
+                };
+                Node::Wave(index)
+            }
+            Some(Token::Number(v)) => Node::Value(v.clone()),
+            Some(Token::Op("(")) => {
+                let node = self.expr(0)?;
+                self.expect(")")?;
+                node
+            }
+            Some(Token::Op("{")) => {
+                let mut parts = vec![self.expr(0)?];
+                while self.peek_op() == Some(",") {
+                    self.pos += 1;
+                    parts.push(self.expr(0)?);
+                }
+                self.expect("}")?;
+                Node::Concat(parts)
+            }
+            _ => return Err(ExprError::new(span, "expected wave name or number")),
+        };
+        self.select(node)
+    }
+
+    /// Part select `[msb:lsb]` or bit select `[bit]` after operand
+    fn select(&mut self, mut node: Node) -> Result<Node, ExprError> {
+        while self.peek_op() == Some("[") {
+            self.pos += 1;
+            let msb = self.index()?;
+            let lsb = if self.peek_op() == Some(":") {
+                self.pos += 1;
+                self.index()?
+            } else {
+                msb
+            };
+            if msb < lsb {
+                return Err(ExprError::new(self.span(), "msb must not be less than lsb"));
+            }
+            self.expect("]")?;
+            node = Node::Select(Box::new(node), msb, lsb);
+        }
+        Ok(node)
+    }
+
+    fn index(&mut self) -> Result<usize, ExprError> {
+        let span = self.span();
+        let v = match self.tokens.get(self.pos) {
+            Some((Token::Number(v), _)) => v.to_u128(),
+            _ => None,
+        };
+        self.pos += 1;
+        v.filter(|v| *v < BitValue::BITS as u128)
+            .map(|v| v as usize)
+            .ok_or_else(|| ExprError::new(span, "expected bit index"))
+    }
+}
+
+fn unknown(size: usize) -> BitValue {
+    let mut v = BitValue::new(size);
+    v.set_unknown();
+    v
+}
+
+/// Logic value of condition, `None` for `x`
+fn truth(v: &BitValue) -> Option<bool> {
+    if v.is_known() {
+        Some(!v.is_zero())
+    } else if v.to_bin().contains('1') {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+fn bit(v: Option<bool>) -> BitValue {
+    match v {
+        Some(b) => BitValue::from_u64(1, b as u64),
+        None => unknown(1),
+    }
+}
+
+fn eval(node: &Node, inputs: &[BitValue]) -> BitValue {
+    match node {
+        Node::Wave(i) => inputs[*i].clone(),
+        Node::Value(v) => v.clone(),
+        Node::Unary(op, a) => {
+            let a = eval(a, inputs);
+            match op {
+                Unary::Not => !a,
+                Unary::LogicNot => bit(truth(&a).map(|b| !b)),
+                Unary::Neg => -a,
+                Unary::Plus => a,
+            }
+        }
+        Node::Binary(op, a, b) => {
+            let (a, b) = (eval(a, inputs), eval(b, inputs));
+            let cmp = |f: fn(Ordering) -> bool| {
+                bit((a.is_known() && b.is_known())
+                    .then(|| a.partial_cmp(&b).map(f))
+                    .flatten())
+            };
+            // Width of shift is width of left operand
+            let shift = |left: bool| match b.to_u128() {
+                Some(n) => {
+                    let n = n.min(a.size() as u128) as usize;
+                    if left {
+                        &a << n
+                    } else {
+                        &a >> n
+                    }
+                }
+                None => unknown(a.size()),
+            };
+            match op {
+                Binary::Mul => a * b,
+                Binary::Add => a + b,
+                Binary::Sub => a - b,
+                Binary::Shl => shift(true),
+                Binary::Shr => shift(false),
+                Binary::Lt => cmp(Ordering::is_lt),
+                Binary::Le => cmp(Ordering::is_le),
+                Binary::Gt => cmp(Ordering::is_gt),
+                Binary::Ge => cmp(Ordering::is_ge),
+                Binary::Eq => cmp(Ordering::is_eq),
+                Binary::Ne => cmp(Ordering::is_ne),
+                Binary::And => a & b,
+                Binary::Xor => a ^ b,
+                Binary::Or => a | b,
+                Binary::LogicAnd => bit(match (truth(&a), truth(&b)) {
+                    (Some(false), _) | (_, Some(false)) => Some(false),
+                    (Some(true), Some(true)) => Some(true),
+                    _ => None,
+                }),
+                Binary::LogicOr => bit(match (truth(&a), truth(&b)) {
+                    (Some(true), _) | (_, Some(true)) => Some(true),
+                    (Some(false), Some(false)) => Some(false),
+                    _ => None,
+                }),
+            }
+        }
+        Node::Select(a, msb, lsb) => eval(a, inputs).slice(*msb, *lsb),
+        Node::Cond(c, a, b) => {
+            let (a, b) = (eval(a, inputs), eval(b, inputs));
+            let size = a.size().max(b.size());
+            match truth(&eval(c, inputs)) {
+                Some(true) => a.zero_extend(size),
+                Some(false) => b.zero_extend(size),
+                None => unknown(size),
+            }
+        }
+        Node::Concat(parts) => {
+            let parts: Vec<BitValue> = parts.iter().map(|p| eval(p, inputs)).collect();
+            let size: usize = parts.iter().map(|p| p.size()).sum();
+            let mut v = BitValue::new(size.min(BitValue::BITS));
+            let mut lsb = 0;
+            for p in parts.iter().rev() {
+                v.set_slice(lsb, p);
+                lsb += p.size();
+            }
+            v
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub(super) struct ExprEdit {
+    pub source: String,
+    #[serde(skip)]
+    pub error: Option<String>,
+}
+
+impl ExprEdit {
+    pub(crate) fn display(&mut self, ui: &mut Ui) -> WindowResult {
+        let mut open = true;
+        let mut state = WindowResult::Open;
+        egui::Window::new("Expression")
+            .open(&mut open)
+            .show(ui.ctx(), |ui| {
+                let response = ui.text_edit_singleline(&mut self.source);
+                if response.changed() {
+                    self.error = match self.source.trim() {
+                        "" => None,
+                        s => Expr::parse(s).err().map(|e| render_error(e, s)),
+                    };
+                }
+                ui.label(egui::RichText::new("Empty expression makes usual wave").small());
+                hseparator!(ui);
+                if let Some(e) = &self.error {
+                    ui.label(e);
+                }
+                if ui
+                    .add_enabled(self.error.is_none(), egui::Button::new("Save"))
+                    .clicked()
+                {
+                    state = WindowResult::Save;
+                }
+            });
+        if !open {
+            state = WindowResult::Cancel;
+        }
+        state
+    }
+}
+
+impl Wave {
+    /// Samples are set by user, not computed
+    pub fn editable(&self) -> bool {
+        self.expr.is_none() && !matches!(self.tp, WaveType::Clock(_))
+    }
+
+    /// Make wave derived from expression or usual one with current values.
+    /// Returns edit reverting the change
+    pub fn set_expression(&mut self, expr: Option<String>) -> super::WaveEdit {
+        let edit = self.content_edit();
+        self.expr = expr;
+        self.expr_error = None;
+        edit
+    }
+
+    /// Take samples computed from expression. Width of values sets type of wave
+    fn set_derived(&mut self, data: Vec<BitValue>) {
+        let size = data.first().map_or(self.reg_size(), |v| v.size());
+        if size != self.reg_size() || matches!(self.tp, WaveType::Clock(_)) {
+            if size == 1 {
+                self.tp = WaveType::Wire;
+                self.display = WaveDisplay::Binary;
+            } else {
+                self.tp = WaveType::Reg(size);
+                self.display = WaveDisplay::Hex;
+            }
+            self.fixed = None;
+            self.enum_table = None;
+        }
+        self.data = data;
+        self.expr_error = None;
+        self.refresh_min_max();
+    }
+}
+
+/// Report with source and labels as text for UI
+pub fn render_error(e: ErrReport, source: &str) -> String {
+    let e = e.with_source_code(source.to_string());
+    let mut report = String::new();
+    let _ = NarratableReportHandler::new().render_report(&mut report, e.as_ref());
+    report
+}
+
+/// Recompute derived waves, inputs of derived wave are computed before it.
+/// Errors are kept in waves
+pub fn update_derived(waves: &mut [Wave]) {
+    let names: Vec<String> = waves.iter().map(|w| w.name()).collect();
+    let mut pending: Vec<(usize, Vec<usize>, Expr)> = Vec::new();
+    for (i, w) in waves.iter_mut().enumerate() {
+        let Some(source) = &w.expr else {
+            continue;
+        };
+        let parsed = Expr::parse(source).and_then(|e| {
+            e.check(source, |n| n != names[i] && names.iter().any(|m| m == n))
+                .map(|_| e)
+        });
+        match parsed {
+            Ok(e) => {
+                let inputs = e
+                    .inputs()
+                    .filter_map(|n| names.iter().position(|m| m == n))
+                    .collect();
+                pending.push((i, inputs, e));
+            }
+            Err(e) => w.expr_error = Some(render_error(e, source)),
+        }
+    }
+    let mut waiting = vec![false; waves.len()];
+    pending.iter().for_each(|(i, _, _)| waiting[*i] = true);
+    while !pending.is_empty() {
+        let before = pending.len();
+        let mut rest = Vec::new();
+        for (i, inputs, e) in pending {
+            if inputs.iter().any(|j| waiting[*j]) {
+                rest.push((i, inputs, e));
+                continue;
+            }
+            let data = (0..waves[i].len())
+                .map(|t| {
+                    let values: Vec<BitValue> = inputs
+                        .iter()
+                        .map(|j| {
+                            let w = &waves[*j];
+                            w.data
+                                .get(t)
+                                .or(w.data.last())
+                                .cloned()
+                                .unwrap_or_else(|| BitValue::new(w.reg_size()))
+                        })
+                        .collect();
+                    e.eval(&values)
+                })
+                .collect();
+            waves[i].set_derived(data);
+            waiting[i] = false;
+        }
+        if rest.len() == before {
+            for (i, _, _) in rest {
+                waves[i].expr_error = Some("Cyclic dependency of expressions".into());
+            }
+            return;
+        }
+        pending = rest;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use egui::Vec2;
+
+    use super::{update_derived, Expr};
+    use crate::app::waves::{BitValue, Wave, WaveType};
+
+    fn eval(s: &str, inputs: &[&BitValue]) -> String {
+        let inputs: Vec<BitValue> = inputs.iter().map(|v| (*v).clone()).collect();
+        Expr::parse(s).unwrap().eval(&inputs).to_literal()
+    }
+
+    #[test]
+    fn test_eval() {
+        let a = BitValue::from_u64(8, 0x12);
+        let b = BitValue::from_u64(1, 1);
+        assert_eq!(eval("a + 4", &[&a]), "32'h00000016");
+        assert_eq!(eval("a[7:4]", &[&a]), "4'h1");
+        assert_eq!(eval("{a[3:0], b, 3'b0}", &[&a, &b]), "8'h28");
+        assert_eq!(eval("a & ~8'h02", &[&a]), "8'h10");
+        assert_eq!(eval("b ? a : 8'hff", &[&b, &a]), "8'h12");
+        assert_eq!(eval("a == 18 && !b", &[&a, &b]), "1'h0");
+        assert_eq!(eval("a << 1 | a >> 4", &[&a]), "8'h25");
+        assert_eq!(eval("2 + 3 * 4 - 1", &[]), "32'h0000000d");
+
+        let mut x = BitValue::new(1);
+        x.set_unknown();
+        assert_eq!(eval("x ? 1'b1 : 1'b0", &[&x]), "1'hx");
+        assert_eq!(eval("x || 1'b1", &[&x]), "1'h1");
+
+        let e = Expr::parse("valid & ~stall | valid").unwrap();
+        assert_eq!(e.inputs().collect::<Vec<_>>(), ["valid", "stall"]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let span = |s: &str| {
+            let e = Expr::parse(s).err().unwrap();
+            let label = e.labels().unwrap().next().unwrap();
+            (label.offset(), label.len())
+        };
+        assert_eq!(span("a + # b"), (4, 1));
+        assert_eq!(span("(a + b"), (6, 0));
+        assert_eq!(span("a b"), (2, 1));
+        assert_eq!(span("a[3:4]"), (5, 1));
+        assert_eq!(span("4'hz1g"), (0, 6));
+        let e = Expr::parse("a + bb").unwrap();
+        let err = e.check("a + bb", |n| n == "a").err().unwrap();
+        assert_eq!(err.labels().unwrap().next().unwrap().offset(), 4);
+    }
+
+    #[test]
+    fn test_derived() {
+        let mut a = Wave::new("a", 3, Vec2::ZERO);
+        a.set_type(WaveType::Reg(4));
+        a.data[1] = BitValue::from_u64(4, 9);
+        let mut sum = Wave::new("sum", 3, Vec2::ZERO);
+        sum.expr = Some("b + 1".into());
+        let mut b = Wave::new("b", 3, Vec2::ZERO);
+        b.expr = Some("a[3:1]".into());
+        let mut waves = vec![sum, a, b];
+        update_derived(&mut waves);
+        let hex = |w: &Wave| w.data.iter().map(|v| v.to_hex()).collect::<Vec<_>>();
+        assert_eq!(hex(&waves[2]), ["0", "4", "0"]);
+        assert_eq!(waves[2].reg_size(), 3);
+        assert_eq!(hex(&waves[0]), ["00000001", "00000005", "00000001"]);
+        assert!(waves[0].expr_error.is_none());
+
+        waves[2].expr = Some("sum".into());
+        update_derived(&mut waves);
+        assert!(waves[0].expr_error.is_some());
+        assert!(waves[2].expr_error.is_some());
+        waves[2].expr = Some("c".into());
+        update_derived(&mut waves);
+        assert!(waves[2]
+            .expr_error
+            .as_ref()
+            .unwrap()
+            .contains("no wave `c`"));
+    }
+}
@@ -0,0 +1,269 @@
+use std::fmt::Display;
+
+use egui::Ui;
+use miette::{ErrReport, LabeledSpan, MietteDiagnostic};
+use serde::{Deserialize, Serialize};
+
+use crate::{app::windows::WindowResult, hseparator};
+
+use super::value::BitValue;
+
+/// Quantization of values between two fixed point steps
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    Floor,
+    TowardZero,
+    /// Ties away from zero
+    Nearest,
+    /// Ties to even, no bias
+    NearestEven,
+}
+
+impl Rounding {
+    const ALL: [Rounding; 4] = [
+        Rounding::Floor,
+        Rounding::TowardZero,
+        Rounding::Nearest,
+        Rounding::NearestEven,
+    ];
+
+    fn apply(&self, v: f64) -> f64 {
+        match self {
+            Rounding::Floor => v.floor(),
+            Rounding::TowardZero => v.trunc(),
+            Rounding::Nearest => v.round(),
+            Rounding::NearestEven if (v - v.trunc()).abs() == 0.5 => (v / 2.0).round() * 2.0,
+            Rounding::NearestEven => v.round(),
+        }
+    }
+}
+
+impl Display for Rounding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rounding::Floor => write!(f, "Floor"),
+            Rounding::TowardZero => write!(f, "Toward zero"),
+            Rounding::Nearest => write!(f, "Nearest"),
+            Rounding::NearestEven => write!(f, "Nearest even"),
+        }
+    }
+}
+
+/// Qm.n format of register: `frac` is n, m is the rest of register bits.
+/// Sign bit isn't counted in m, so signed 16 bits with 15 fraction bits is Q0.15
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct FixedPoint {
+    pub frac: usize,
+    pub signed: bool,
+    pub rounding: Rounding,
+}
+
+impl FixedPoint {
+    pub fn new(size: usize) -> Self {
+        Self {
+            frac: size.saturating_sub(1),
+            signed: true,
+            rounding: Rounding::NearestEven,
+        }
+    }
+
+    /// Format name like `sQ0.15` for register of `size` bits
+    pub fn name(&self, size: usize) -> String {
+        format!(
+            "{}Q{}.{}",
+            if self.signed { "s" } else { "u" },
+            size.saturating_sub(self.frac + self.signed as usize),
+            self.frac
+        )
+    }
+
+    /// Real value. `x` and `z` bits counted as 0
+    pub fn real(&self, v: &BitValue) -> f64 {
+        // Only top bits matter for f64, drop low ones of long value so integer can't overflow
+        let shift = v.size().saturating_sub(1000);
+        if shift == 0 {
+            return ldexp(v.to_f64(self.signed), -(self.frac as i64));
+        }
+        let v = if self.signed {
+            v.ashr(shift)
+        } else {
+            v >> shift
+        };
+        ldexp(v.to_f64(self.signed), shift as i64 - self.frac as i64)
+    }
+
+    /// Real value for editor, `x`/`z` printed like verilog `%d`
+    pub fn print(&self, v: &BitValue) -> String {
+        if v.is_known() {
+            self.real(v).to_string()
+        } else {
+            v.to_dec(self.signed)
+        }
+    }
+
+    /// Quantize decimal fraction like `0.75` or `-1e-3` into `value` with format rounding.
+    /// Anything else parsed as raw bits by [`BitValue::parse_from`]
+    pub fn parse(&self, value: &mut BitValue, size: usize, s: &str) -> Result<(), ErrReport> {
+        let Ok(v) = s.trim().parse::<f64>() else {
+            return value.parse_from(s);
+        };
+        // Scaled value must fit f64, scale by long fraction partly by shift of bits
+        let shift = self.frac.saturating_sub(960);
+        let (frac, bits) = ((self.frac - shift) as i64, (size - shift) as i64);
+        let q = self.rounding.apply(ldexp(v, frac));
+        let (min, max) = if self.signed {
+            (-ldexp(1.0, bits - 1), ldexp(1.0, bits - 1))
+        } else {
+            (0.0, ldexp(1.0, bits))
+        };
+        match BitValue::from_f64(size, q).filter(|_| min <= q && q < max) {
+            Some(q) => {
+                value.assign(&q << shift);
+                Ok(())
+            }
+            None => Err(ErrReport::new(
+                MietteDiagnostic::new("Error by parsing value")
+                    .with_code(s)
+                    .with_severity(miette::Severity::Error)
+                    .with_help(format!(
+                        "Value must be from {} to {}",
+                        ldexp(min, -frac),
+                        ldexp(max - 1.0, -frac)
+                    ))
+                    .with_label(LabeledSpan::at(
+                        (0, s.len()),
+                        format!("out of {} range", self.name(size)),
+                    )),
+            )),
+        }
+    }
+}
+
+/// `v * 2^e` without overflow of power itself
+fn ldexp(mut v: f64, mut e: i64) -> f64 {
+    while e > 1000 {
+        v *= 2f64.powi(1000);
+        e -= 1000;
+    }
+    while e < -1000 {
+        v *= 2f64.powi(-1000);
+        e += 1000;
+    }
+    v * 2f64.powi(e as i32)
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub(super) struct FixedChange {
+    pub size: usize,
+    pub enabled: bool,
+    pub fixed: FixedPoint,
+}
+
+impl FixedChange {
+    pub(crate) fn display(&mut self, ui: &mut Ui) -> WindowResult {
+        let mut open = true;
+        let mut state = WindowResult::Open;
+        egui::Window::new("Fixed point")
+            .open(&mut open)
+            .show(ui.ctx(), |ui| {
+                ui.vertical(|ui| {
+                    ui.checkbox(&mut self.enabled, "Fixed point value");
+                    ui.add_enabled_ui(self.enabled, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label("Fraction bits");
+                            ui.add(
+                                egui::DragValue::new(&mut self.fixed.frac)
+                                    .clamp_range(0..=self.size),
+                            );
+                        });
+                        ui.checkbox(&mut self.fixed.signed, "Signed");
+                        egui::ComboBox::new("fixed_rounding", "Rounding")
+                            .selected_text(self.fixed.rounding.to_string())
+                            .show_ui(ui, |ui| {
+                                Rounding::ALL.into_iter().for_each(|v| {
+                                    ui.selectable_value(&mut self.fixed.rounding, v, v.to_string());
+                                });
+                            });
+                        ui.label(format!("Format: {}", self.fixed.name(self.size)));
+                    });
+                });
+                hseparator!(ui);
+                if ui.button("Save").clicked() {
+                    state = WindowResult::Save;
+                }
+            });
+        if !open {
+            state = WindowResult::Cancel;
+        }
+        state
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FixedPoint, Rounding};
+    use crate::app::waves::BitValue;
+
+    fn quantize(fixed: &FixedPoint, size: usize, s: &str) -> Option<String> {
+        let mut v = BitValue::new(size);
+        fixed.parse(&mut v, size, s).ok().map(|_| v.to_hex())
+    }
+
+    #[test]
+    fn test_q15() {
+        let mut q = FixedPoint::new(16);
+        assert_eq!(q.name(16), "sQ0.15");
+        assert_eq!(quantize(&q, 16, "0.5").as_deref(), Some("4000"));
+        assert_eq!(quantize(&q, 16, "-1").as_deref(), Some("8000"));
+        assert_eq!(quantize(&q, 16, "1"), None);
+        assert_eq!(quantize(&q, 16, "16'h7fff").as_deref(), Some("7fff"));
+
+        let mut v = BitValue::new(16);
+        v.parse_from("16'hc000").unwrap();
+        assert_eq!(q.real(&v), -0.5);
+        assert_eq!(q.print(&v), "-0.5");
+        q.signed = false;
+        assert_eq!(q.name(16), "uQ1.15");
+        assert_eq!(q.real(&v), 1.5);
+    }
+
+    #[test]
+    fn test_rounding() {
+        let mut q = FixedPoint {
+            frac: 1,
+            signed: true,
+            rounding: Rounding::Floor,
+        };
+        // Steps of 0.5, values between steps
+        let cases = [
+            ("0.75", ["1", "1", "2", "2"]),
+            ("0.25", ["0", "0", "1", "0"]),
+            ("-0.25", ["f", "0", "f", "0"]),
+            ("-0.75", ["e", "f", "e", "e"]),
+            ("1.2", ["2", "2", "2", "2"]),
+        ];
+        for (s, expected) in cases {
+            for (r, e) in Rounding::ALL.into_iter().zip(expected) {
+                q.rounding = r;
+                assert_eq!(quantize(&q, 4, s).as_deref(), Some(e), "{} {}", s, r);
+            }
+        }
+        assert_eq!(quantize(&q, 4, "4").as_deref(), None);
+        assert_eq!(quantize(&q, 4, "-4").as_deref(), Some("8"));
+    }
+
+    #[test]
+    fn test_wide() {
+        let q = FixedPoint {
+            frac: 1100,
+            signed: false,
+            rounding: Rounding::Nearest,
+        };
+        let mut v = BitValue::new(1200);
+        v.set_bit(1099, crate::app::waves::value::Logic::V1);
+        assert_eq!(q.real(&v), 0.5);
+        let mut p = BitValue::new(1200);
+        q.parse(&mut p, 1200, "0.5").unwrap();
+        assert_eq!(p, v);
+    }
+}
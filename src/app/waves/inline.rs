@@ -0,0 +1,138 @@
+//! Edit of single cycle typed from keyboard, without edit window.
+
+use egui::{plot::PlotPoint, Rect};
+use miette::ErrReport;
+
+use super::{
+    edit::WaveEdit, expr::render_error, fixed::FixedPoint, BitValue, Wave, WaveDisplay, WaveType,
+};
+
+/// Value typed by user in format of wave display
+pub(super) fn parse_sample(
+    value: &mut BitValue,
+    s: &str,
+    display: WaveDisplay,
+    fixed: Option<&FixedPoint>,
+) -> Result<(), ErrReport> {
+    match (display, fixed) {
+        (WaveDisplay::Float, _) => value.parse_float(s),
+        (WaveDisplay::Ascii, _) => value.parse_ascii(s),
+        (WaveDisplay::Decimal(_) | WaveDisplay::Analog(_), Some(f)) => {
+            f.parse(value, value.size(), s)
+        }
+        _ => value.parse_from(s),
+    }
+}
+
+/// Text of value in format of wave display, parsed back by [`parse_sample`]
+pub(super) fn print_sample(
+    value: &BitValue,
+    display: WaveDisplay,
+    fixed: Option<&FixedPoint>,
+) -> String {
+    let size = value.size();
+    match (display, fixed) {
+        (WaveDisplay::Decimal(_) | WaveDisplay::Analog(_), Some(f)) => f.print(value),
+        (WaveDisplay::Binary | WaveDisplay::Enum, _) => format!("{}'b{}", size, value.to_bin()),
+        (WaveDisplay::Hex, _) => format!("{}'h{}", size, value.to_hex()),
+        (WaveDisplay::Decimal(s) | WaveDisplay::Analog(s), _) => value.to_dec(s.signed()),
+        (WaveDisplay::Ascii, _) => value.to_ascii_literal(),
+        (WaveDisplay::Float, _) => match value.to_float() {
+            Some(f) if size == 32 => (f as f32).to_string(),
+            Some(f) => f.to_string(),
+            None => format!("{}'h{}", size, value.to_hex()),
+        },
+    }
+}
+
+impl Wave {
+    /// Wave of one bit, set by `0` and `1` keys
+    pub fn is_wire(&self) -> bool {
+        matches!(self.tp, WaveType::Wire)
+    }
+
+    /// Screen rect of cycle in plot of last frame
+    pub fn cycle_rect(&self, cycle: usize) -> Option<Rect> {
+        let t = self.transform?;
+        let x = |c: usize| t.position_from_point(&PlotPoint::new(c as f64, 0.0)).x;
+        let (y, frame) = (t.frame().y_range(), t.frame());
+        let rect = Rect::from_x_y_ranges(x(cycle)..=x(cycle + 1), y);
+        frame.intersects(rect).then(|| rect.intersect(*frame))
+    }
+
+    /// Value of cycle as text for editor
+    pub fn sample_text(&self, cycle: usize) -> String {
+        let Some(v) = self.data.get(cycle) else {
+            return String::new();
+        };
+        match (&self.enum_table, self.display) {
+            (Some(t), WaveDisplay::Enum) => t.print(v),
+            _ => print_sample(v, self.display, self.fixed.as_ref()),
+        }
+    }
+
+    /// Value of cycle from text in display format or name of enum state
+    pub fn parse_sample(&self, cycle: usize, s: &str) -> Result<BitValue, String> {
+        let state = self
+            .enum_table
+            .as_ref()
+            .and_then(|t| t.states.iter().find(|st| st.name == s.trim()));
+        if let Some(state) = state {
+            return Ok(BitValue::from_u64(self.reg_size(), state.value));
+        }
+        let mut v = self
+            .data
+            .get(cycle)
+            .cloned()
+            .unwrap_or_else(|| BitValue::new(self.reg_size()));
+        parse_sample(&mut v, s, self.display, self.fixed.as_ref())
+            .map_err(|e| render_error(e, s))?;
+        Ok(v)
+    }
+
+    /// Set one cycle, edit goes to undo history
+    pub fn set_sample(&mut self, cycle: usize, v: BitValue) {
+        if !self.editable() {
+            return;
+        }
+        let Some(d) = self.data.get_mut(cycle) else {
+            return;
+        };
+        let old = std::mem::replace(d, v);
+        self.edits.push(WaveEdit::Samples(vec![(cycle, old)]));
+        self.refresh_min_max();
+    }
+
+    /// Invert bit of one bit wave, `x` and `z` become `1`
+    pub fn toggle_sample(&mut self, cycle: usize) {
+        let Some(mut v) = self.data.get(cycle).cloned() else {
+            return;
+        };
+        let high = !(v.is_known() && v.bool());
+        v.set_bool(high);
+        self.set_sample(cycle, v);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use egui::Vec2;
+
+    use crate::app::waves::{BitValue, Wave, WaveType};
+
+    #[test]
+    fn test_inline() {
+        let mut w = Wave::new("a", 4, Vec2::ZERO);
+        w.toggle_sample(1);
+        assert_eq!(w.data[1], BitValue::from_u64(1, 1));
+        w.toggle_sample(1);
+        assert_eq!(w.data[1], BitValue::from_u64(1, 0));
+        assert_eq!(w.take_edits().len(), 2);
+
+        w.set_type(WaveType::Reg(8));
+        let v = w.parse_sample(2, "'h2a").unwrap();
+        w.set_sample(2, v);
+        assert_eq!(w.sample_text(2), "8'h2a");
+        assert!(w.parse_sample(2, "9'h1").is_err());
+    }
+}
@@ -1,12 +1,25 @@
+mod bus;
+mod clipboard;
+mod clock;
+mod cycles;
+mod domain;
+mod edit;
+mod enums;
+mod expr;
+mod fixed;
+mod inline;
+mod pattern;
+mod search;
+mod selection;
 mod state_edit;
 mod type_change;
 mod value;
 mod wtype;
 
-use std::{collections::HashSet, io::Write, path::PathBuf};
+use std::{collections::HashSet, io::Write, ops::Range, path::PathBuf, rc::Rc};
 
 use egui::{
-    plot::{AxisBools, Line, PlotPoint, PlotPoints, Polygon},
+    plot::{AxisBools, Line, PlotPoint, PlotPoints, PlotTransform, Polygon, Text},
     InputState, Pos2, Ui, Vec2,
 };
 use log::debug;
@@ -14,10 +27,30 @@ use serde::{Deserialize, Serialize};
 
 use crate::hseparator;
 
-use self::{state_edit::StateEdit, type_change::TypeChange};
-use super::windows::WindowResult;
+use self::{
+    bus::{BusSlice, BusSplit},
+    cycles::CycleInsert,
+    domain::DomainClock,
+    enums::{EnumEdit, EnumTable},
+    expr::ExprEdit,
+    fixed::{FixedChange, FixedPoint},
+    pattern::{Pattern, PatternFill},
+    state_edit::StateEdit,
+    type_change::TypeChange,
+};
+use super::{markers::Markers, time::TimeScale, windows::WindowResult};
 
-pub use {value::BitValue, wtype::WaveType};
+pub use {
+    bus::{sync_buses, WaveAction, WaveId, WaveMove},
+    clipboard::handle_clipboard,
+    clock::update_clocks,
+    domain::clock_domains,
+    edit::WaveEdit,
+    expr::update_derived,
+    search::Query,
+    value::BitValue,
+    wtype::WaveType,
+};
 
 #[derive(Serialize, Deserialize, Clone, Copy)]
 enum WaveSign {
@@ -40,6 +73,12 @@ enum WaveDisplay {
     Hex,
     Decimal(WaveSign),
     Analog(WaveSign),
+    /// IEEE-754 of 16, 32 or 64 bits register
+    Float,
+    /// Names from states table
+    Enum,
+    /// Packed ASCII characters
+    Ascii,
 }
 
 impl WaveDisplay {
@@ -49,6 +88,28 @@ impl WaveDisplay {
             WaveDisplay::Hex => false,
             WaveDisplay::Decimal(s) => s.signed(),
             WaveDisplay::Analog(s) => s.signed(),
+            WaveDisplay::Float => true,
+            WaveDisplay::Enum => false,
+            WaveDisplay::Ascii => false,
+        }
+    }
+
+    /// Value for plot. Not finite float plotted as 0.
+    /// Decimal and analog show real value of fixed point format with its sign
+    pub fn value(&self, v: &BitValue, fixed: Option<&FixedPoint>) -> f64 {
+        match (self, fixed) {
+            (WaveDisplay::Float, _) => v.to_float().filter(|f| f.is_finite()).unwrap_or(0.0),
+            (WaveDisplay::Decimal(_) | WaveDisplay::Analog(_), Some(f)) => f.real(v),
+            (d, _) => v.to_f64(d.signed()),
+        }
+    }
+
+    /// Text drawn over plot segment
+    fn label(&self, v: &BitValue, table: Option<&EnumTable>) -> Option<String> {
+        match (self, table) {
+            (WaveDisplay::Enum, Some(t)) => Some(t.print(v)),
+            (WaveDisplay::Ascii, _) => Some(v.to_ascii()),
+            _ => None,
         }
     }
 }
@@ -56,24 +117,76 @@ impl WaveDisplay {
 #[derive(Serialize, Deserialize)]
 enum WaveState {
     Show,
-    Edit(StateEdit),
+    Edit(Box<StateEdit>),
     TypeChange(TypeChange),
+    FixedChange(FixedChange),
+    EnumEdit(EnumEdit),
+    PatternFill(PatternFill),
+    BusSplit(BusSplit),
+    ExprEdit(ExprEdit),
+    CycleInsert(CycleInsert),
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct Wave {
+    #[serde(default)]
+    id: WaveId,
     state: WaveState,
     tp: WaveType,
     display: WaveDisplay,
+    #[serde(default)]
+    fixed: Option<FixedPoint>,
+    #[serde(default)]
+    enum_table: Option<EnumTable>,
+    /// Last used fill pattern, keeps seed of random patterns
+    #[serde(default)]
+    pattern: Option<Pattern>,
+    /// Linked part of other wave
+    #[serde(default)]
+    slice: Option<BusSlice>,
+    /// Expression over other waves computing samples
+    #[serde(default)]
+    expr: Option<String>,
+    /// Group in main panel
+    #[serde(default)]
+    group: Option<WaveId>,
+    /// Clock wave sampling this one in testbench
+    #[serde(default)]
+    domain: Option<WaveId>,
     name: String,
+    #[serde(with = "value::rle")]
     data: Vec<BitValue>,
-    plot_data: Vec<f64>,
-    viewed_data: Vec<String>,
     selected_data: HashSet<usize>,
     max_value: f64,
     min_value: f64,
     deleted: bool,
     pub current_size: Vec2,
+    /// Not yet taken to undo history
+    #[serde(skip)]
+    edits: Vec<WaveEdit>,
+    /// Plot position on screen of last frame
+    #[serde(skip)]
+    transform: Option<PlotTransform>,
+    #[serde(skip)]
+    batch_value: Option<BitValue>,
+    #[serde(skip)]
+    action: Option<WaveAction>,
+    #[serde(skip)]
+    expr_error: Option<String>,
+    /// Cycle under pointer when plot was hovered last time
+    #[serde(skip)]
+    last_hovered: Option<usize>,
+    /// Runs of cycles matching search
+    #[serde(skip)]
+    found: Vec<Range<usize>>,
+    /// Time scale of project in last frame
+    #[serde(skip)]
+    scale: TimeScale,
+    #[serde(skip)]
+    domain_clock: Option<DomainClock>,
+    /// Waves able to enable clock, shared by all waves
+    #[serde(skip)]
+    gate_choices: Rc<Vec<(WaveId, String)>>,
 }
 
 type WaveHandler = dyn FnMut(&mut Wave, &mut Ui);
@@ -82,34 +195,49 @@ impl Wave {
     pub fn new<T: Into<String>>(name: T, size: usize, ui_size: Vec2) -> Self {
         let mut data = Vec::with_capacity(size);
         data.resize(size, BitValue::new(1));
-        let mut plot_data = Vec::with_capacity(size);
-        plot_data.resize(size, 0.0);
-        let mut viewed_data = Vec::with_capacity(size);
-        viewed_data.resize(size, "0".into());
         debug!("New data size: {}", data.len());
         Self {
+            id: WaveId::default(),
             state: WaveState::Show,
             tp: WaveType::Wire,
             display: WaveDisplay::Binary,
+            fixed: None,
+            enum_table: None,
+            pattern: None,
+            slice: None,
+            expr: None,
+            group: None,
+            domain: None,
             name: name.into(),
             data,
-            plot_data,
-            viewed_data,
             selected_data: HashSet::new(),
             max_value: 0.0,
             min_value: 0.0,
             deleted: false,
             current_size: ui_size,
+            edits: Vec::new(),
+            transform: None,
+            batch_value: None,
+            action: None,
+            expr_error: None,
+            last_hovered: None,
+            found: Vec::new(),
+            scale: TimeScale::default(),
+            domain_clock: None,
+            gate_choices: Rc::default(),
         }
     }
 
-    pub fn display(&mut self, ui: &mut Ui, link_group_id: egui::Id, user_input: &InputState) {
-        self.display_inner::<Box<WaveHandler>>(
-            ui,
-            link_group_id,
-            user_input,
-            None,
-        );
+    pub fn display(
+        &mut self,
+        ui: &mut Ui,
+        link_group_id: egui::Id,
+        user_input: &InputState,
+        markers: &Markers,
+        scale: &TimeScale,
+    ) {
+        self.scale = *scale;
+        self.display_inner::<Box<WaveHandler>>(ui, link_group_id, user_input, Some(markers), None);
     }
 
     pub fn display_with_info<F>(
@@ -121,7 +249,7 @@ impl Wave {
     ) where
         F: FnMut(&mut Wave, &mut Ui),
     {
-        self.display_inner(ui, link_group_id, user_input, Some(info_draw));
+        self.display_inner(ui, link_group_id, user_input, None, Some(info_draw));
     }
 
     fn display_inner<F>(
@@ -129,16 +257,23 @@ impl Wave {
         ui: &mut Ui,
         link_group_id: egui::Id,
         user_input: &InputState,
+        markers: Option<&Markers>,
         info_draw: Option<F>,
     ) where
         F: FnMut(&mut Wave, &mut Ui),
     {
         ui.horizontal(|ui| {
-            ui.allocate_ui(self.current_size, |ui| {
+            let area = ui.allocate_ui(self.current_size, |ui| {
                 let name = self.name.clone();
+                let in_project = info_draw.is_none();
                 ui.vertical(|ui| {
-                    ui.text_edit_singleline(&mut self.name)
-                        .context_menu(|ui| self.name_menu(ui));
+                    ui.horizontal(|ui| {
+                        if in_project {
+                            self.drag_handle(ui);
+                        }
+                        ui.text_edit_singleline(&mut self.name)
+                            .context_menu(|ui| self.name_menu(ui));
+                    });
                     if let Some(mut f) = info_draw {
                         f(self, ui);
                     } else {
@@ -150,10 +285,22 @@ impl Wave {
                     diff = ts.size as f64 * 2.0;
                 };
 
-                let plot_response = egui::plot::Plot::new(name)
+                let mut hovered = None;
+                // Start of cycle nearest to pointer
+                let mut boundary = None;
+                let mut plot = egui::plot::Plot::new(name);
+                if in_project {
+                    let scale = self.scale;
+                    plot = plot.x_axis_formatter(move |x, _| scale.format(x));
+                }
+                let plot_response = plot
                     .link_axis(link_group_id, true, false)
                     .link_cursor(link_group_id, true, true)
-                    .allow_drag(AxisBools::new(true, false))
+                    // Ctrl and Shift drags select cycles
+                    .allow_drag(AxisBools::new(
+                        !(user_input.modifiers.ctrl || user_input.modifiers.shift),
+                        false,
+                    ))
                     .allow_scroll(false)
                     .allow_zoom(AxisBools::new(true, false))
                     .auto_bounds_y()
@@ -161,23 +308,31 @@ impl Wave {
                     .show(ui, |plot_ui| {
                         let mut max = self.max_value;
                         let mut min = self.min_value;
+                        let middle = (max + min) / 2.0;
+                        let (display, fixed) = (self.display, self.fixed);
+                        let level = |v: &BitValue| {
+                            if v.is_high_z() {
+                                middle
+                            } else {
+                                display.value(v, fixed.as_ref())
+                            }
+                        };
                         let idata = self.data.iter().enumerate();
                         let plot: PlotPoints = match self.display {
-                            WaveDisplay::Binary | WaveDisplay::Hex => idata
+                            WaveDisplay::Binary
+                            | WaveDisplay::Hex
+                            | WaveDisplay::Decimal(_)
+                            | WaveDisplay::Float
+                            | WaveDisplay::Enum
+                            | WaveDisplay::Ascii => idata
                                 .flat_map(|(i, v)| {
-                                    let t = v.to_f64(false);
+                                    let t = level(v);
                                     [[i as f64, t], [(i + 1) as f64, t]]
                                 })
                                 .collect(),
-                            WaveDisplay::Decimal(s) => idata
-                                .flat_map(|(i, v)| {
-                                    let t = v.to_f64(s.signed());
-                                    [[i as f64, t], [(i + 1) as f64, t]]
-                                })
-                                .collect(),
-                            WaveDisplay::Analog(s) => idata
+                            WaveDisplay::Analog(_) => idata
                                 .map(|(i, v)| {
-                                    let t = v.to_f64(s.signed());
+                                    let t = level(v);
                                     [i as f64, t]
                                 })
                                 .collect(),
@@ -190,8 +345,74 @@ impl Wave {
 
                         let line = Line::new(plot);
                         plot_ui.line(line);
+
+                        let labeled = matches!(
+                            (display, &self.enum_table),
+                            (WaveDisplay::Enum, Some(_)) | (WaveDisplay::Ascii, _)
+                        );
+                        if labeled {
+                            let bounds = plot_ui.plot_bounds();
+                            let first = (bounds.min()[0].max(0.0) as usize).min(self.data.len());
+                            let last = (bounds.max()[0].max(0.0) as usize + 1).min(self.data.len());
+                            // Names of too many segments overlap each other
+                            if last - first <= 1000 {
+                                let mut start = first;
+                                for i in first + 1..=last {
+                                    if i < last && self.data[i] == self.data[start] {
+                                        continue;
+                                    }
+                                    let v = &self.data[start];
+                                    let label = display.label(v, self.enum_table.as_ref());
+                                    if let Some(label) = label {
+                                        let point = PlotPoint::new(start as f64, level(v));
+                                        let text = Text::new(point, label)
+                                            .anchor(egui::Align2::LEFT_BOTTOM);
+                                        plot_ui.text(text.name(""));
+                                    }
+                                    start = i;
+                                }
+                            }
+                        }
+
+                        for r in runs(&self.data, |v| v.is_high_z()) {
+                            let line = Line::new(PlotPoints::new(vec![
+                                [r.start as f64, middle],
+                                [r.end as f64, middle],
+                            ]))
+                            .color(egui::Color32::from_rgb(200, 170, 30))
+                            .width(2.0);
+                            plot_ui.line(line.name(""));
+                        }
+                        for r in runs(&self.data, |v| v.has_unknown()) {
+                            let polygon = Polygon::new(PlotPoints::Owned(vec![
+                                PlotPoint::new(r.start as f64, max),
+                                PlotPoint::new(r.end as f64, max),
+                                PlotPoint::new(r.end as f64, min),
+                                PlotPoint::new(r.start as f64, min),
+                            ]))
+                            .color(egui::Color32::from_rgba_unmultiplied(200, 20, 20, 90));
+                            plot_ui.polygon(polygon.name(""));
+                        }
+                        for r in &self.found {
+                            let polygon = Polygon::new(PlotPoints::Owned(vec![
+                                PlotPoint::new(r.start as f64, max),
+                                PlotPoint::new(r.end as f64, max),
+                                PlotPoint::new(r.end as f64, min),
+                                PlotPoint::new(r.start as f64, min),
+                            ]))
+                            .color(egui::Color32::from_rgba_unmultiplied(220, 200, 40, 90));
+                            plot_ui.polygon(polygon.name(""));
+                        }
+                        if let Some(c) = &self.domain_clock {
+                            c.draw(plot_ui, min, max);
+                        }
+                        if let Some(m) = markers {
+                            m.draw(plot_ui, max);
+                        }
                         if let Some(p) = plot_ui.pointer_coordinate() {
                             if p.x >= 0.0 && p.x <= self.data.len() as f64 {
+                                boundary = Some(p.x.round() as usize);
+                                hovered = Some(p.x.floor() as usize);
                                 let polygon = Polygon::new(PlotPoints::Owned(vec![
                                     //TODO: переделать в выделение максимума/минимума
                                     PlotPoint::new(p.x.floor(), max),
@@ -204,7 +425,7 @@ impl Wave {
                                 if user_input.key_pressed(egui::Key::A) {
                                     self.selected_data.clear();
                                 }
-                                if plot_ui.plot_secondary_clicked() {
+                                if plot_ui.plot_secondary_clicked() && self.editable() {
                                     if user_input.modifiers.ctrl {
                                         self.selected_data.insert(p.x.floor() as usize);
                                     } else {
@@ -218,7 +439,7 @@ impl Wave {
                                                 "Mouse position: {:?}",
                                                 user_input.pointer.hover_pos()
                                             );
-                                            self.state = WaveState::Edit(StateEdit {
+                                            self.state = WaveState::Edit(Box::new(StateEdit {
                                                 index: p.x.floor() as usize,
                                                 init_value: v.clone(),
                                                 pos: user_input
@@ -227,9 +448,11 @@ impl Wave {
                                                     .unwrap_or(Pos2 { x: 0.0, y: 0.0 }),
                                                 tp: self.tp,
                                                 display: self.display,
+                                                fixed: self.fixed,
+                                                enum_table: self.enum_table.clone(),
                                                 current_value: None,
                                                 error: None,
-                                            });
+                                            }));
                                         }
                                     }
                                 }
@@ -237,12 +460,12 @@ impl Wave {
                         }
 
                         if !self.selected_data.is_empty() {
-                            self.selected_data.iter().for_each(|i| {
+                            self.selected_runs().into_iter().for_each(|r| {
                                 let polygon = Polygon::new(PlotPoints::Owned(vec![
-                                    PlotPoint::new(*i as f64, max),
-                                    PlotPoint::new((*i + 1) as f64, max),
-                                    PlotPoint::new((*i + 1) as f64, min),
-                                    PlotPoint::new(*i as f64, min),
+                                    PlotPoint::new(r.start as f64, max),
+                                    PlotPoint::new(r.end as f64, max),
+                                    PlotPoint::new(r.end as f64, min),
+                                    PlotPoint::new(r.start as f64, min),
                                 ]))
                                 .color(egui::Color32::from_rgba_unmultiplied(150, 30, 30, 125));
                                 plot_ui.polygon(polygon.name(""));
@@ -258,28 +481,88 @@ impl Wave {
                             plot_ui.polygon(polygon.name(""));
                         }
                     });
+                self.transform = Some(plot_response.transform);
                 if plot_response.response.double_clicked() {
                     self.refresh_min_max();
                 }
+                if hovered.is_some() {
+                    self.last_hovered = hovered;
+                }
+                if let Some(cycle) = boundary.filter(|_| markers.is_some()) {
+                    self.marker_keys(ui, cycle, user_input);
+                }
                 self.display_window_edit(ui);
                 self.display_type_change(ui);
+                self.display_fixed_change(ui);
+                self.display_enum_edit(ui);
+                self.display_pattern_fill(ui);
+                self.display_bus_split(ui);
+                self.display_expr_edit(ui);
+                self.display_cycle_insert(ui);
             });
+            if ui.rect_contains_pointer(area.response.rect) && user_input.modifiers.alt {
+                if user_input.key_pressed(egui::Key::ArrowUp) {
+                    self.action = Some(WaveAction::Move(WaveMove::Up));
+                } else if user_input.key_pressed(egui::Key::ArrowDown) {
+                    self.action = Some(WaveAction::Move(WaveMove::Down));
+                }
+            }
         });
     }
 
+    /// Marker and cursors placed by keys at `cycle` under pointer
+    fn marker_keys(&mut self, ui: &Ui, cycle: usize, input: &InputState) {
+        if ui.memory(|m| m.focus().is_some()) || !input.modifiers.is_none() {
+            return;
+        }
+        if input.key_pressed(egui::Key::M) {
+            self.action = Some(WaveAction::Marker(cycle));
+        } else if input.key_pressed(egui::Key::Num1) {
+            self.action = Some(WaveAction::Cursor(0, cycle));
+        } else if input.key_pressed(egui::Key::Num2) {
+            self.action = Some(WaveAction::Cursor(1, cycle));
+        }
+    }
+
+    /// Handle to drag wave to other place of project
+    fn drag_handle(&mut self, ui: &mut Ui) {
+        let handle = ui
+            .add(egui::Label::new("☰").sense(egui::Sense::drag()))
+            .on_hover_text("Drag to move, Alt+Up/Down over wave moves it by one");
+        if handle.dragged() {
+            self.action = Some(WaveAction::Drag);
+            ui.ctx().set_cursor_icon(egui::CursorIcon::Grabbing);
+        } else if handle.drag_released() {
+            self.action = Some(WaveAction::Drop);
+        } else if handle.hovered() {
+            ui.ctx().set_cursor_icon(egui::CursorIcon::Grab);
+        }
+    }
+
     fn display_window_edit(&mut self, ui: &mut Ui) {
         if let WaveState::Edit(edit) = &mut self.state {
             match edit.window_edit(ui, &self.selected_data) {
                 WindowResult::Open => {}
                 WindowResult::Save => {
+                    let indexes: Vec<usize> = if self.selected_data.is_empty() {
+                        vec![edit.index]
+                    } else {
+                        self.selected_data.iter().copied().collect()
+                    };
+                    let samples = indexes
+                        .iter()
+                        .map(|i| (*i, self.data[*i].clone()))
+                        .collect();
+                    self.edits.push(WaveEdit::Samples(samples));
                     if !self.selected_data.is_empty() {
                         self.selected_data.iter().for_each(|i| {
                             self.data[*i] = edit.init_value.clone();
                         });
+                        self.batch_value = Some(edit.init_value.clone());
                     } else {
                         self.data[edit.index] = edit.init_value.clone();
                     }
-                    let vf = edit.init_value.to_f64(self.display.signed());
+                    let vf = self.display.value(&edit.init_value, self.fixed.as_ref());
                     if vf > self.max_value {
                         self.max_value = vf;
                     }
@@ -301,10 +584,9 @@ impl Wave {
     fn recalculate_clock(&mut self) {
         if let WaveType::Clock(c) = self.tp {
             self.data.iter_mut().enumerate().for_each(|(i, v)| {
-                let i = i + c.phase;
                 v.set_size(1).unwrap();
                 v.set_zero();
-                v.set_bool((i % c.period) < c.duty);
+                v.set_bool(c.level(i));
             });
         }
     }
@@ -327,21 +609,258 @@ impl Wave {
             }
         }
         if let Some(nt) = new_type {
+            self.record_content();
             self.set_type(nt);
         }
     }
 
+    fn display_fixed_change(&mut self, ui: &mut Ui) {
+        if let WaveState::FixedChange(params) = &mut self.state {
+            match params.display(ui) {
+                WindowResult::Open => {}
+                WindowResult::Save => {
+                    self.fixed = params.enabled.then_some(params.fixed);
+                    self.state = WaveState::Show;
+                    self.refresh_min_max();
+                }
+                WindowResult::Cancel | WindowResult::Close | WindowResult::Error(_) => {
+                    self.state = WaveState::Show;
+                }
+            }
+        }
+    }
+
+    fn display_enum_edit(&mut self, ui: &mut Ui) {
+        if let WaveState::EnumEdit(params) = &mut self.state {
+            match params.display(ui) {
+                WindowResult::Open => {}
+                WindowResult::Save => {
+                    if params.table.states.is_empty() {
+                        self.enum_table = None;
+                        if let WaveDisplay::Enum = self.display {
+                            self.display = WaveDisplay::Hex;
+                        }
+                    } else {
+                        self.enum_table = Some(params.table.clone());
+                        self.display = WaveDisplay::Enum;
+                    }
+                    self.state = WaveState::Show;
+                    self.refresh_min_max();
+                }
+                WindowResult::Cancel | WindowResult::Close | WindowResult::Error(_) => {
+                    self.state = WaveState::Show;
+                }
+            }
+        }
+    }
+
+    fn display_pattern_fill(&mut self, ui: &mut Ui) {
+        if let WaveState::PatternFill(params) = &mut self.state {
+            match params.display(ui) {
+                WindowResult::Open => {}
+                WindowResult::Save => {
+                    let pattern = params.pattern;
+                    self.state = WaveState::Show;
+                    self.fill_pattern(pattern);
+                }
+                WindowResult::Cancel | WindowResult::Close | WindowResult::Error(_) => {
+                    self.state = WaveState::Show;
+                }
+            }
+        }
+    }
+
+    fn display_bus_split(&mut self, ui: &mut Ui) {
+        let name = self.name();
+        if let WaveState::BusSplit(params) = &mut self.state {
+            match params.display(ui, &name) {
+                WindowResult::Open => {}
+                WindowResult::Save => {
+                    let params = params.clone();
+                    self.action =
+                        Some(WaveAction::Split(self.split(&params.slices, params.linked)));
+                    self.state = WaveState::Show;
+                }
+                WindowResult::Cancel | WindowResult::Close | WindowResult::Error(_) => {
+                    self.state = WaveState::Show;
+                }
+            }
+        }
+    }
+
+    fn display_expr_edit(&mut self, ui: &mut Ui) {
+        if let WaveState::ExprEdit(params) = &mut self.state {
+            match params.display(ui) {
+                WindowResult::Open => {}
+                WindowResult::Save => {
+                    let source = params.source.trim();
+                    let expr = (!source.is_empty()).then(|| source.to_string());
+                    self.state = WaveState::Show;
+                    let edit = self.set_expression(expr);
+                    self.edits.push(edit);
+                }
+                WindowResult::Cancel | WindowResult::Close | WindowResult::Error(_) => {
+                    self.state = WaveState::Show;
+                }
+            }
+        }
+    }
+
+    fn display_cycle_insert(&mut self, ui: &mut Ui) {
+        if let WaveState::CycleInsert(params) = &mut self.state {
+            match params.display(ui) {
+                WindowResult::Open => {}
+                WindowResult::Save => {
+                    let (edit, all) = (params.edit(), params.all);
+                    self.state = WaveState::Show;
+                    if all || !self.owns_cycles() {
+                        self.action = Some(WaveAction::Cycles(edit));
+                    } else {
+                        self.record_content();
+                        self.edit_cycles(&edit, true);
+                    }
+                }
+                WindowResult::Cancel | WindowResult::Close | WindowResult::Error(_) => {
+                    self.state = WaveState::Show;
+                }
+            }
+        }
+    }
+
+    /// Fill selected cycles by pattern in time order
+    fn fill_pattern(&mut self, pattern: Pattern) {
+        let mut cycles: Vec<usize> = self
+            .selected_data
+            .iter()
+            .copied()
+            .filter(|i| *i < self.data.len())
+            .collect();
+        cycles.sort_unstable();
+        let values = pattern.generate(self.reg_size(), cycles.len());
+        let samples = cycles.iter().map(|i| (*i, self.data[*i].clone())).collect();
+        self.edits.push(WaveEdit::Samples(samples));
+        for (i, v) in cycles.into_iter().zip(values) {
+            self.data[i].assign(v);
+        }
+        self.pattern = Some(pattern);
+        self.refresh_min_max();
+    }
+
     fn name_menu(&mut self, ui: &mut Ui) {
+        // Type of linked slice is set by bus, of derived wave by expression
+        let typed = self.slice.is_none() && self.expr.is_none();
+        ui.add_enabled_ui(typed, |ui| self.type_menu(ui));
+        ui.menu_button("Change display", |ui| self.display_menu(ui));
+        let fill = !self.selected_data.is_empty() && self.editable();
+        if ui
+            .add_enabled(fill, egui::Button::new("Fill selection"))
+            .clicked()
+        {
+            self.state = WaveState::PatternFill(PatternFill {
+                count: self.selected_data.len(),
+                pattern: self.pattern.unwrap_or_default(),
+            });
+        }
+        hseparator!(ui);
+        if let WaveType::Reg(size) = self.tp {
+            if ui.button("Split bus").clicked() {
+                self.state = WaveState::BusSplit(BusSplit {
+                    size,
+                    slices: BusSplit::bits(&self.name(), size),
+                    linked: true,
+                });
+            }
+        }
+        if ui
+            .add_enabled(self.editable(), egui::Button::new("Merge into bus"))
+            .clicked()
+        {
+            self.action = Some(WaveAction::Merge);
+        }
+        let derive = self.slice.is_none() && !matches!(self.tp, WaveType::Clock(_));
+        if ui
+            .add_enabled(derive, egui::Button::new("Expression"))
+            .clicked()
+        {
+            self.state = WaveState::ExprEdit(ExprEdit {
+                source: self.expr.clone().unwrap_or_default(),
+                error: None,
+            });
+        }
+        if self.slice.is_some() && ui.button("Unlink from bus").clicked() {
+            self.unlink();
+        }
+        ui.menu_button("Cycles", |ui| self.cycles_menu(ui));
+        hseparator!(ui);
+        ui.menu_button("Move", |ui| {
+            for (label, to) in [
+                ("Up", WaveMove::Up),
+                ("Down", WaveMove::Down),
+                ("To top", WaveMove::Top),
+                ("To bottom", WaveMove::Bottom),
+            ] {
+                if ui.button(label).clicked() {
+                    self.action = Some(WaveAction::Move(to));
+                    ui.close_menu();
+                }
+            }
+        });
+        if ui.button("Move to group").clicked() {
+            self.action = Some(WaveAction::Group);
+        }
+        let sampled = !self.is_clock();
+        if ui
+            .add_enabled(sampled, egui::Button::new("Clock domain"))
+            .clicked()
+        {
+            self.action = Some(WaveAction::Domain);
+        }
+        if ui.button("Delete").clicked() {
+            self.deleted = true;
+        }
+    }
+
+    fn cycles_menu(&mut self, ui: &mut Ui) {
+        let own = self.owns_cycles();
+        if ui.button("Insert").clicked() {
+            let mut first = self.selected_data.iter().min().copied();
+            first = first.or(self.last_hovered);
+            self.state = WaveState::CycleInsert(CycleInsert {
+                at: first.unwrap_or(0),
+                count: 1,
+                after: false,
+                all: !own,
+                max: self.data.len().saturating_sub(1),
+            });
+            ui.close_menu();
+        }
+        let selected = !self.selected_data.is_empty();
+        if ui
+            .add_enabled(selected && own, egui::Button::new("Delete selected"))
+            .clicked()
+        {
+            let edit = self.delete_selected();
+            self.record_content();
+            self.edit_cycles(&edit, true);
+            ui.close_menu();
+        }
+        let all = egui::Button::new("Delete selected in all waves");
+        if ui.add_enabled(selected, all).clicked() {
+            self.action = Some(WaveAction::Cycles(self.delete_selected()));
+            ui.close_menu();
+        }
+    }
+
+    fn type_menu(&mut self, ui: &mut Ui) {
         ui.menu_button("Change Type", |ui| {
             if ui.button("Wire").clicked() {
-                self.data.iter_mut().for_each(|v| {
-                    v.set_size(1).unwrap();
-                    self.display = WaveDisplay::Binary;
-                    self.tp = WaveType::Wire;
-                });
+                self.record_content();
+                self.set_type(WaveType::Wire);
                 return;
             };
             if ui.button("Clock").clicked() {
+                let own = |(id, _): &&(WaveId, String)| *id != self.id;
+                let gates = self.gate_choices.iter().filter(own).cloned().collect();
                 self.state = WaveState::TypeChange(TypeChange {
                     current_tp: self.tp,
                     new_tp: if let WaveType::Clock(_) = self.tp {
@@ -350,6 +869,8 @@ impl Wave {
                         WaveType::Clock(wtype::Clock::new())
                     },
                     max_size: self.data.len(),
+                    scale: self.scale,
+                    gates,
                 });
                 return;
             }
@@ -358,38 +879,78 @@ impl Wave {
                     current_tp: self.tp,
                     new_tp: WaveType::Reg(1),
                     max_size: self.data.len(),
+                    scale: self.scale,
+                    gates: Vec::new(),
                 });
             }
         });
-        ui.menu_button("Change display", |ui| {
-            if ui.button("Binary").clicked() {
-                self.display = WaveDisplay::Binary;
-                return;
+    }
+
+    fn display_menu(&mut self, ui: &mut Ui) {
+        if ui.button("Binary").clicked() {
+            self.display = WaveDisplay::Binary;
+            return;
+        }
+        if ui.button("Hex").clicked() {
+            self.display = WaveDisplay::Hex;
+            return;
+        }
+        ui.menu_button("Decimal", |ui| {
+            if ui.button("Unsigned").clicked() {
+                self.display = WaveDisplay::Decimal(WaveSign::Unsigned);
             }
-            if ui.button("Hex").clicked() {
-                self.display = WaveDisplay::Hex;
-                return;
+            if ui.button("Signed").clicked() {
+                self.display = WaveDisplay::Decimal(WaveSign::Signed);
             }
-            ui.menu_button("Decimal", |ui| {
-                if ui.button("Unsigned").clicked() {
-                    self.display = WaveDisplay::Decimal(WaveSign::Unsigned);
-                }
-                if ui.button("Signed").clicked() {
-                    self.display = WaveDisplay::Decimal(WaveSign::Signed);
-                }
-            });
-            ui.menu_button("Analog", |ui| {
-                if ui.button("Unsigned").clicked() {
-                    self.display = WaveDisplay::Analog(WaveSign::Unsigned);
-                }
-                if ui.button("Signed").clicked() {
-                    self.display = WaveDisplay::Analog(WaveSign::Signed);
-                }
-            });
         });
-        hseparator!(ui);
-        if ui.button("Delete").clicked() {
-            self.deleted = true;
+        ui.menu_button("Analog", |ui| {
+            if ui.button("Unsigned").clicked() {
+                self.display = WaveDisplay::Analog(WaveSign::Unsigned);
+            }
+            if ui.button("Signed").clicked() {
+                self.display = WaveDisplay::Analog(WaveSign::Signed);
+            }
+        });
+        if ui
+            .add_enabled(self.enum_table.is_some(), egui::Button::new("Enum"))
+            .clicked()
+        {
+            self.display = WaveDisplay::Enum;
+            self.refresh_min_max();
+        }
+        if ui
+            .add_enabled(self.reg_size() >= 8, egui::Button::new("ASCII"))
+            .clicked()
+        {
+            self.display = WaveDisplay::Ascii;
+            self.refresh_min_max();
+        }
+        let float = BitValue::is_float_size(self.reg_size());
+        if ui.add_enabled(float, egui::Button::new("Float")).clicked() {
+            self.display = WaveDisplay::Float;
+            self.refresh_min_max();
+        }
+        if let WaveType::Reg(size) = self.tp {
+            if ui.button("Fixed point").clicked() {
+                self.state = WaveState::FixedChange(FixedChange {
+                    size,
+                    enabled: true,
+                    fixed: self.fixed.unwrap_or_else(|| FixedPoint::new(size)),
+                });
+            }
+            let states = size <= EnumTable::MAX_BITS;
+            if ui
+                .add_enabled(states, egui::Button::new("States"))
+                .clicked()
+            {
+                self.state = WaveState::EnumEdit(EnumEdit {
+                    size,
+                    table: self
+                        .enum_table
+                        .clone()
+                        .unwrap_or_else(|| EnumTable::new(&self.name())),
+                });
+            }
         }
     }
 
@@ -397,6 +958,8 @@ impl Wave {
         match new_type {
             WaveType::Clock(c) => {
                 self.tp = WaveType::Clock(c);
+                self.fixed = None;
+                self.enum_table = None;
                 self.recalculate_clock();
                 self.max_value = 1.0;
                 self.min_value = 0.0;
@@ -407,6 +970,8 @@ impl Wave {
                     v.set_size(1).unwrap();
                 });
                 self.tp = WaveType::Wire;
+                self.fixed = None;
+                self.enum_table = None;
                 self.display = WaveDisplay::Binary;
                 self.refresh_min_max();
             }
@@ -415,6 +980,12 @@ impl Wave {
                     v.set_size(r).unwrap();
                 });
                 self.tp = WaveType::Reg(r);
+                if let Some(f) = &mut self.fixed {
+                    f.frac = f.frac.min(r);
+                }
+                if r > EnumTable::MAX_BITS {
+                    self.enum_table = None;
+                }
                 self.display = WaveDisplay::Hex;
                 self.refresh_min_max();
             }
@@ -424,7 +995,47 @@ impl Wave {
     fn draw_info(&mut self, ui: &mut Ui) {
         ui.vertical(|ui| {
             ui.label(&format!("Bit size: {}", self.reg_size()));
-            ui.label(&format!("Type: {}", self.tp));
+            let tp = match self.tp {
+                WaveType::Clock(c) => {
+                    let time = |v: usize| self.scale.format(v as f64);
+                    format!(
+                        "Clock: period->{}, duty->{}, phase->{}",
+                        time(c.period),
+                        time(c.duty),
+                        time(c.phase)
+                    )
+                }
+                tp => tp.to_string(),
+            };
+            ui.label(&format!("Type: {}", tp));
+            if let WaveType::Clock(c) = self.tp {
+                self.draw_clock_info(ui, c);
+            }
+            if let Some(f) = &self.fixed {
+                ui.label(format!(
+                    "Format: {} {}",
+                    f.name(self.reg_size()),
+                    f.rounding
+                ));
+            }
+            if let Some(s) = &self.slice {
+                ui.label(format!("Linked to bus [{}:{}]", s.msb, s.lsb));
+            }
+            if let Some(e) = &self.expr {
+                ui.label(format!("= {}", e));
+            }
+            if let Some(e) = &self.expr_error {
+                ui.colored_label(egui::Color32::RED, e);
+            }
+            match (&self.domain_clock, self.domain()) {
+                (Some(c), _) => {
+                    ui.colored_label(c.color, format!("Domain: {}", c.name));
+                }
+                (None, Some(_)) => {
+                    ui.colored_label(egui::Color32::RED, "Clock of domain is missing");
+                }
+                (None, None) => {}
+            }
         });
     }
 
@@ -432,6 +1043,27 @@ impl Wave {
         self.deleted
     }
 
+    /// Mark wave to be removed from project
+    pub fn delete(&mut self) {
+        self.deleted = true;
+    }
+
+    pub fn group(&self) -> Option<WaveId> {
+        self.group
+    }
+
+    /// Move wave to other group. Returns edit reverting the move
+    pub fn set_group(&mut self, group: Option<WaveId>) -> WaveEdit {
+        let edit = self.content_edit();
+        self.group = group;
+        edit
+    }
+
+    /// Bring back wave deleted by user
+    pub fn restore(&mut self) {
+        self.deleted = false;
+    }
+
     pub fn set_len(&mut self, len: usize) {
         if self.data.len() < len {
             self.data.resize(len, BitValue::new(1));
@@ -451,6 +1083,16 @@ impl Wave {
         self.data.resize(new_len, last);
     }
 
+    /// Cut or extend by last value to `len`, for waves made out of project
+    pub fn fit_len(&mut self, len: usize) {
+        if self.data.len() < len {
+            self.extend_by_last(len);
+        } else {
+            self.data.truncate(len);
+        }
+        self.refresh_min_max();
+    }
+
     pub fn set_last_value(&mut self, new_value: BitValue) {
         if let Some(v) = self.data.last_mut() {
             *v = new_value;
@@ -461,7 +1103,7 @@ impl Wave {
         self.max_value = f64::NEG_INFINITY;
         self.min_value = f64::INFINITY;
         self.data.iter().for_each(|v| {
-            let fv = v.to_f64(self.display.signed());
+            let fv = self.display.value(v, self.fixed.as_ref());
             if fv > self.max_value {
                 self.max_value = fv;
             }
@@ -479,10 +1121,32 @@ impl Wave {
         }
     }
 
+    /// Show register as packed ASCII string
+    pub fn show_ascii(&mut self) {
+        self.display = WaveDisplay::Ascii;
+    }
+
+    /// Type of signal: enum type for wave with states table
+    pub fn export_signal_type(&self) -> String {
+        match &self.enum_table {
+            Some(t) => t.type_name.clone(),
+            None => self.export_type(),
+        }
+    }
+
+    /// `typedef enum` of states table
+    pub fn export_enum(&self) -> Option<String> {
+        self.enum_table.as_ref().map(|t| t.export(self.reg_size()))
+    }
+
     pub fn name(&self) -> String {
         self.name.clone().replace(' ', "_")
     }
 
+    pub fn set_name<T: Into<String>>(&mut self, name: T) {
+        self.name = name.into();
+    }
+
     pub fn len(&self) -> usize {
         self.data.len()
     }
@@ -496,9 +1160,10 @@ impl Wave {
         }
     }
 
-    pub fn generate_memb(&self, path: PathBuf) -> Result<(), std::io::Error> {
+    /// Memory file of samples taken by domain `clock`
+    pub fn generate_memb(&self, path: PathBuf, clock: Option<&Wave>) -> Result<(), std::io::Error> {
         let mut fl = std::fs::File::create(path)?;
-        for v in &self.data {
+        for v in &self.domain_samples(clock) {
             fl.write_all(v.to_bin().as_bytes()).unwrap();
             fl.write_all(b"\n").unwrap();
         }
@@ -506,3 +1171,21 @@ impl Wave {
         Ok(())
     }
 }
+
+/// Ranges of neighbour values for which `f` is true
+fn runs<F>(data: &[BitValue], f: F) -> Vec<Range<usize>>
+where
+    F: Fn(&BitValue) -> bool,
+{
+    let mut res: Vec<Range<usize>> = Vec::new();
+    for (i, v) in data.iter().enumerate() {
+        if !f(v) {
+            continue;
+        }
+        match res.last_mut() {
+            Some(r) if r.end == i => r.end = i + 1,
+            _ => res.push(i..i + 1),
+        }
+    }
+    res
+}
@@ -0,0 +1,333 @@
+use std::fmt::Display;
+
+use egui::Ui;
+use serde::{Deserialize, Serialize};
+
+use crate::{app::windows::WindowResult, hseparator};
+
+use super::value::{BitValue, Logic};
+
+/// Standard PRBS polynomials of ITU-T O.150
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Prbs {
+    /// x^7 + x^6 + 1
+    Prbs7,
+    /// x^15 + x^14 + 1
+    Prbs15,
+    /// x^31 + x^28 + 1
+    Prbs31,
+}
+
+impl Prbs {
+    const ALL: [Prbs; 3] = [Prbs::Prbs7, Prbs::Prbs15, Prbs::Prbs31];
+
+    /// Length of register and second tap
+    fn taps(&self) -> (u32, u32) {
+        match self {
+            Prbs::Prbs7 => (7, 6),
+            Prbs::Prbs15 => (15, 14),
+            Prbs::Prbs31 => (31, 28),
+        }
+    }
+}
+
+impl Display for Prbs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PRBS{}", self.taps().0)
+    }
+}
+
+/// Generator of values for selected cycles. Same pattern always gives same values
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Pattern {
+    /// `start + step * n`, restarts from `start` after `wrap` values. Zero `wrap`
+    /// wraps by register width only
+    Counter {
+        start: i64,
+        step: i64,
+        wrap: u64,
+    },
+    /// Linear from `from` at first cycle to `to` at last one
+    Ramp {
+        from: i64,
+        to: i64,
+    },
+    Constant {
+        value: i64,
+    },
+    WalkingOne,
+    WalkingZero,
+    /// Gray code of cycle number
+    Gray,
+    /// Bits of LFSR from `seed`, register takes next `size` bits each cycle
+    Prbs {
+        kind: Prbs,
+        seed: u32,
+    },
+    /// Pseudo random values from `seed`
+    Random {
+        seed: u64,
+    },
+}
+
+impl Pattern {
+    /// One of each kind with default parameters
+    const ALL: [Pattern; 8] = [
+        Pattern::Counter {
+            start: 0,
+            step: 1,
+            wrap: 0,
+        },
+        Pattern::Ramp { from: 0, to: 255 },
+        Pattern::Constant { value: 0 },
+        Pattern::WalkingOne,
+        Pattern::WalkingZero,
+        Pattern::Gray,
+        Pattern::Prbs {
+            kind: Prbs::Prbs7,
+            seed: 1,
+        },
+        Pattern::Random { seed: 1 },
+    ];
+
+    /// `count` values of register of `size` bits
+    pub fn generate(&self, size: usize, count: usize) -> Vec<BitValue> {
+        match *self {
+            Pattern::Counter { start, step, wrap } => (0..count)
+                .map(|n| {
+                    let n = if wrap == 0 { n as u64 } else { n as u64 % wrap };
+                    BitValue::from_i128(size, start as i128 + step as i128 * n as i128)
+                })
+                .collect(),
+            Pattern::Ramp { from, to } => {
+                let last = count.saturating_sub(1).max(1) as i128;
+                (0..count)
+                    .map(|n| {
+                        let v = from as i128 + (to as i128 - from as i128) * n as i128 / last;
+                        BitValue::from_i128(size, v)
+                    })
+                    .collect()
+            }
+            Pattern::Constant { value } => vec![BitValue::from_i128(size, value as i128); count],
+            Pattern::WalkingOne | Pattern::WalkingZero => (0..count)
+                .map(|n| {
+                    let one = &BitValue::from_u64(size, 1) << (n % size.max(1));
+                    if let Pattern::WalkingZero = self {
+                        !one
+                    } else {
+                        one
+                    }
+                })
+                .collect(),
+            Pattern::Gray => (0..count)
+                .map(|n| BitValue::from_u128(size, (n ^ (n >> 1)) as u128))
+                .collect(),
+            Pattern::Prbs { kind, seed } => {
+                let (len, tap) = kind.taps();
+                let mask = (1u32 << len) - 1;
+                let mut state = if seed & mask == 0 { mask } else { seed & mask };
+                let mut next_bit = || {
+                    let bit = ((state >> (len - 1)) ^ (state >> (tap - 1))) & 1;
+                    state = ((state << 1) | bit) & mask;
+                    bit == 1
+                };
+                (0..count)
+                    .map(|_| {
+                        let mut v = BitValue::new(size);
+                        for b in (0..size).rev() {
+                            if next_bit() {
+                                v.set_bit(b, Logic::V1);
+                            }
+                        }
+                        v
+                    })
+                    .collect()
+            }
+            Pattern::Random { seed } => {
+                let mut state = seed;
+                let words = (size + 63) / 64;
+                (0..count)
+                    .map(|_| {
+                        (0..words).fold(BitValue::new(size), |v, _| {
+                            (&v << 64) | BitValue::from_u64(size, splitmix64(&mut state))
+                        })
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+impl Default for Pattern {
+    /// Counter from 0 by 1
+    fn default() -> Self {
+        Pattern::ALL[0]
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pattern::Counter { .. } => write!(f, "Counter"),
+            Pattern::Ramp { .. } => write!(f, "Ramp"),
+            Pattern::Constant { .. } => write!(f, "Constant"),
+            Pattern::WalkingOne => write!(f, "Walking one"),
+            Pattern::WalkingZero => write!(f, "Walking zero"),
+            Pattern::Gray => write!(f, "Gray code"),
+            Pattern::Prbs { .. } => write!(f, "PRBS"),
+            Pattern::Random { .. } => write!(f, "Random"),
+        }
+    }
+}
+
+/// Next value of SplitMix64 generator
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub(super) struct PatternFill {
+    pub count: usize,
+    pub pattern: Pattern,
+}
+
+impl PatternFill {
+    pub(crate) fn display(&mut self, ui: &mut Ui) -> WindowResult {
+        let mut open = true;
+        let mut state = WindowResult::Open;
+        egui::Window::new("Fill pattern")
+            .open(&mut open)
+            .show(ui.ctx(), |ui| {
+                ui.label(format!("Selected cycles: {}", self.count));
+                egui::ComboBox::new("fill_pattern", "Pattern")
+                    .selected_text(self.pattern.to_string())
+                    .show_ui(ui, |ui| {
+                        Pattern::ALL.into_iter().for_each(|p| {
+                            let same =
+                                std::mem::discriminant(&p) == std::mem::discriminant(&self.pattern);
+                            if ui.selectable_label(same, p.to_string()).clicked() && !same {
+                                self.pattern = p;
+                            }
+                        });
+                    });
+                let field = |ui: &mut Ui, label: &str, v: &mut i64| {
+                    ui.horizontal(|ui| {
+                        ui.label(label);
+                        ui.add(egui::DragValue::new(v));
+                    });
+                };
+                match &mut self.pattern {
+                    Pattern::Counter { start, step, wrap } => {
+                        field(ui, "Start", start);
+                        field(ui, "Step", step);
+                        ui.horizontal(|ui| {
+                            ui.label("Wrap after");
+                            ui.add(egui::DragValue::new(wrap));
+                            ui.label(egui::RichText::new("0 wraps by width").small());
+                        });
+                    }
+                    Pattern::Ramp { from, to } => {
+                        field(ui, "From", from);
+                        field(ui, "To", to);
+                    }
+                    Pattern::Constant { value } => field(ui, "Value", value),
+                    Pattern::WalkingOne | Pattern::WalkingZero | Pattern::Gray => {}
+                    Pattern::Prbs { kind, seed } => {
+                        egui::ComboBox::new("fill_prbs", "Polynomial")
+                            .selected_text(kind.to_string())
+                            .show_ui(ui, |ui| {
+                                Prbs::ALL.into_iter().for_each(|v| {
+                                    ui.selectable_value(kind, v, v.to_string());
+                                });
+                            });
+                        ui.horizontal(|ui| {
+                            ui.label("Seed");
+                            ui.add(egui::DragValue::new(seed));
+                        });
+                    }
+                    Pattern::Random { seed } => {
+                        ui.horizontal(|ui| {
+                            ui.label("Seed");
+                            ui.add(egui::DragValue::new(seed));
+                        });
+                    }
+                }
+                hseparator!(ui);
+                if ui.button("Save").clicked() {
+                    state = WindowResult::Save;
+                }
+            });
+        if !open {
+            state = WindowResult::Cancel;
+        }
+        state
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Pattern, Prbs};
+
+    fn hex(p: Pattern, size: usize, count: usize) -> Vec<String> {
+        p.generate(size, count).iter().map(|v| v.to_hex()).collect()
+    }
+
+    #[test]
+    fn test_patterns() {
+        let counter = Pattern::Counter {
+            start: 14,
+            step: 1,
+            wrap: 0,
+        };
+        assert_eq!(hex(counter, 4, 4), ["e", "f", "0", "1"]);
+        let counter = Pattern::Counter {
+            start: 1,
+            step: -2,
+            wrap: 3,
+        };
+        assert_eq!(hex(counter, 4, 4), ["1", "f", "d", "1"]);
+        let ramp = Pattern::Ramp { from: 0, to: 10 };
+        assert_eq!(hex(ramp, 8, 3), ["00", "05", "0a"]);
+        assert_eq!(hex(ramp, 8, 1), ["00"]);
+        assert_eq!(hex(Pattern::Constant { value: -1 }, 4, 2), ["f", "f"]);
+        assert_eq!(hex(Pattern::WalkingOne, 3, 4), ["1", "2", "4", "1"]);
+        assert_eq!(hex(Pattern::WalkingZero, 3, 2), ["6", "5"]);
+        assert_eq!(hex(Pattern::Gray, 3, 5), ["0", "1", "3", "2", "6"]);
+    }
+
+    #[test]
+    fn test_prbs() {
+        for kind in [Prbs::Prbs7, Prbs::Prbs15] {
+            let p = Pattern::Prbs { kind, seed: 1 };
+            let period = (1usize << kind.taps().0) - 1;
+            let bits: Vec<bool> = p.generate(1, period * 2).iter().map(|v| v.bool()).collect();
+            assert_eq!(bits[..period], bits[period..]);
+            // Maximal length sequence has one more 1 than 0
+            assert_eq!(
+                bits[..period].iter().filter(|b| **b).count(),
+                period / 2 + 1
+            );
+        }
+        let p = Pattern::Prbs {
+            kind: Prbs::Prbs7,
+            seed: 0x7f,
+        };
+        // Known start of PRBS7 from all ones
+        assert_eq!(hex(p, 8, 3), ["02", "0c", "28"]);
+        assert_eq!(hex(p, 1, 8).concat(), "00000010");
+    }
+
+    #[test]
+    fn test_random() {
+        let p = Pattern::Random { seed: 42 };
+        let a = p.generate(200, 4);
+        assert_eq!(a, p.generate(200, 4));
+        assert_ne!(a[0], a[1]);
+        assert_ne!(a, Pattern::Random { seed: 43 }.generate(200, 4));
+        assert!(hex(p, 12, 100).iter().all(|h| h.len() == 3));
+    }
+}
@@ -0,0 +1,129 @@
+//! Search of cycles by value of wave, edges of single bit waves and changes.
+
+use std::{cmp::Ordering, ops::Range};
+
+use super::{expr::render_error, runs, value::Logic, BitValue, Wave, WaveType};
+
+/// Condition of cycle, values are in verilog syntax or names of enum states
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Query {
+    Equal(String),
+    Differ(String),
+    /// Inclusive range of values, signed for signed display of wave
+    Within(String, String),
+    Rising,
+    Falling,
+    /// Value differs from previous cycle
+    Change,
+}
+
+impl Wave {
+    /// Runs of cycles matching `query`, edges and changes are single cycles
+    pub fn find(&self, query: &Query) -> Result<Vec<Range<usize>>, String> {
+        let res = match query {
+            Query::Equal(v) => {
+                let v = self.parse_value(v)?;
+                runs(&self.data, |d| *d == v)
+            }
+            Query::Differ(v) => {
+                let v = self.parse_value(v)?;
+                runs(&self.data, |d| *d != v)
+            }
+            Query::Within(low, high) => {
+                let (low, high) = (self.parse_value(low)?, self.parse_value(high)?);
+                let signed = self.display.signed();
+                let cmp = |a: &BitValue, b: &BitValue| match signed {
+                    true => a.cmp_signed(b),
+                    false => a.partial_cmp(b),
+                };
+                runs(&self.data, |d| {
+                    matches!(cmp(d, &low), Some(Ordering::Greater | Ordering::Equal))
+                        && matches!(cmp(d, &high), Some(Ordering::Less | Ordering::Equal))
+                })
+            }
+            Query::Rising | Query::Falling => {
+                if !matches!(self.tp, WaveType::Wire | WaveType::Clock(_)) {
+                    return Err(format!("{} is not Wire or Clock", self.name));
+                }
+                let (from, to) = match query {
+                    Query::Rising => (Logic::V0, Logic::V1),
+                    _ => (Logic::V1, Logic::V0),
+                };
+                // Verilog edges: 0 to x or z and x or z to 1 are rising too
+                self.edges(|a, b| a != b && (a == from || b == to))
+            }
+            Query::Change => self.edges(|a, b| a != b),
+        };
+        Ok(res)
+    }
+
+    /// Cycles where value differs from previous one and `edge` holds for
+    /// previous and current bit 0
+    fn edges<F: Fn(Logic, Logic) -> bool>(&self, edge: F) -> Vec<Range<usize>> {
+        let single = matches!(self.tp, WaveType::Wire | WaveType::Clock(_));
+        (1..self.data.len())
+            .filter(|i| {
+                let (a, b) = (&self.data[i - 1], &self.data[*i]);
+                match single {
+                    true => edge(a.get_bit(0), b.get_bit(0)),
+                    false => a != b,
+                }
+            })
+            .map(|i| i..i + 1)
+            .collect()
+    }
+
+    fn parse_value(&self, s: &str) -> Result<BitValue, String> {
+        let s = s.trim();
+        let state = self
+            .enum_table
+            .as_ref()
+            .and_then(|t| t.states.iter().find(|st| st.name == s));
+        if let Some(state) = state {
+            return Ok(BitValue::from_u64(self.reg_size(), state.value));
+        }
+        BitValue::from_literal(s, self.reg_size()).map_err(|e| render_error(e, s))
+    }
+
+    /// Highlight cycles found by search
+    pub fn set_found(&mut self, found: Vec<Range<usize>>) {
+        self.found = found;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use egui::Vec2;
+
+    use super::Query;
+    use crate::app::waves::{BitValue, Wave, WaveType};
+
+    fn starts(w: &Wave, query: Query) -> Vec<usize> {
+        w.find(&query).unwrap().iter().map(|r| r.start).collect()
+    }
+
+    #[test]
+    fn test_find() {
+        let mut w = Wave::new("a", 6, Vec2::ZERO);
+        w.set_type(WaveType::Reg(4));
+        for (i, v) in [1, 1, 5, 9, 5, 5].into_iter().enumerate() {
+            w.data[i] = BitValue::from_u64(4, v);
+        }
+        let found = w.find(&Query::Equal("5".into())).unwrap();
+        assert_eq!(found, vec![2..3, 4..6]);
+        assert_eq!(starts(&w, Query::Differ("4'h5".into())), [0, 3]);
+        let within = Query::Within("2".into(), "'h9".into());
+        assert_eq!(w.find(&within).unwrap(), vec![2..6]);
+        assert_eq!(starts(&w, Query::Change), [2, 3, 4]);
+        assert!(w.find(&Query::Rising).is_err());
+        assert!(w.find(&Query::Equal("z1".into())).is_err());
+
+        let mut wire = Wave::new("b", 6, Vec2::ZERO);
+        for (i, v) in "01x100".chars().enumerate() {
+            wire.data[i].parse_from(&format!("1'b{}", v)).unwrap();
+        }
+        assert_eq!(starts(&wire, Query::Rising), [1, 3]);
+        assert_eq!(starts(&wire, Query::Falling), [2, 4]);
+        assert_eq!(starts(&wire, Query::Change), [1, 2, 3, 4]);
+    }
+}
@@ -0,0 +1,107 @@
+//! Selection of cycles controlled from outside of wave: drag over several waves,
+//! shift-click and batch edit of all selected waves.
+
+use std::ops::{Range, RangeInclusive};
+
+use egui::Pos2;
+
+use super::{BitValue, Wave, WaveEdit};
+
+impl Wave {
+    /// Select only cycles of `range`
+    pub fn select(&mut self, range: RangeInclusive<usize>) {
+        self.selected_data.clear();
+        let end = (*range.end() + 1).min(self.data.len());
+        self.selected_data.extend(*range.start()..end);
+    }
+
+    pub fn has_selection(&self) -> bool {
+        !self.selected_data.is_empty()
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.selected_data.clear();
+    }
+
+    /// Wave isn't drawn in this frame, so its plot can't be pointed
+    pub fn hide(&mut self) {
+        self.transform = None;
+    }
+
+    /// Pointer is over plot of wave
+    pub fn plot_contains(&self, pos: Pos2) -> bool {
+        self.transform.map_or(false, |t| t.frame().contains(pos))
+    }
+
+    /// Cycle under pointer in height of plot. Pointer out of sides gives first or last cycle
+    pub fn cycle_at(&self, pos: Pos2) -> Option<usize> {
+        let t = self.transform?;
+        let last = self.data.len().checked_sub(1)?;
+        if !t.frame().y_range().contains(&pos.y) {
+            return None;
+        }
+        let x = t.value_from_position(pos).x.max(0.0);
+        Some((x as usize).min(last))
+    }
+
+    /// Sorted runs of neighbour selected cycles
+    pub(super) fn selected_runs(&self) -> Vec<Range<usize>> {
+        let mut selected: Vec<usize> = self.selected_data.iter().copied().collect();
+        selected.sort_unstable();
+        let mut res: Vec<Range<usize>> = Vec::new();
+        for i in selected {
+            match res.last_mut() {
+                Some(r) if r.end == i => r.end = i + 1,
+                _ => res.push(i..i + 1),
+            }
+        }
+        res
+    }
+
+    /// Value saved by batch edit of this wave, to be set in other selected waves
+    pub fn take_batch_value(&mut self) -> Option<BitValue> {
+        self.batch_value.take()
+    }
+
+    /// Set all selected cycles to `v` converted to wave width
+    pub fn fill_selection(&mut self, v: &BitValue) {
+        if self.selected_data.is_empty() || !self.editable() {
+            return;
+        }
+        let mut v = v.clone();
+        v.set_size(self.reg_size()).ok();
+        let mut samples = Vec::with_capacity(self.selected_data.len());
+        for i in self.selected_data.iter().copied() {
+            if let Some(d) = self.data.get_mut(i) {
+                samples.push((i, d.clone()));
+                d.assign(v.clone());
+            }
+        }
+        self.edits.push(WaveEdit::Samples(samples));
+        self.refresh_min_max();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use egui::Vec2;
+
+    use crate::app::waves::{BitValue, Wave, WaveType};
+
+    #[test]
+    fn test_select_fill() {
+        let mut w = Wave::new("a", 10, Vec2::ZERO);
+        w.set_type(WaveType::Reg(4));
+        w.select(2..=4);
+        w.selected_data.insert(8);
+        w.selected_data.insert(9);
+        assert_eq!(w.selected_runs(), [2..5, 8..10]);
+        w.select(8..=20);
+        assert_eq!(w.selected_runs(), vec![8..10]);
+
+        w.fill_selection(&BitValue::from_u64(8, 0x3c));
+        let hex: Vec<String> = w.data.iter().map(|v| v.to_hex()).collect();
+        assert_eq!(hex, ["0", "0", "0", "0", "0", "0", "0", "0", "c", "c"]);
+        assert_eq!(w.take_edits().len(), 1);
+    }
+}
@@ -1,12 +1,19 @@
 use std::collections::HashSet;
 
 use egui::{Pos2, Ui};
-use miette::ErrReport;
+use miette::{ErrReport, NarratableReportHandler};
 use serde::{Deserialize, Serialize};
 
 use crate::app::windows::WindowResult;
 
-use super::{value::BitValue, wtype::WaveType, WaveDisplay};
+use super::{
+    enums::EnumTable,
+    fixed::FixedPoint,
+    inline::{parse_sample, print_sample},
+    value::BitValue,
+    wtype::WaveType,
+    WaveDisplay,
+};
 
 #[derive(Serialize, Deserialize)]
 pub(super) struct StateEdit {
@@ -15,6 +22,10 @@ pub(super) struct StateEdit {
     pub pos: Pos2,
     pub tp: WaveType,
     pub display: WaveDisplay,
+    #[serde(default)]
+    pub fixed: Option<FixedPoint>,
+    #[serde(default)]
+    pub enum_table: Option<EnumTable>,
     pub current_value: Option<String>,
     #[serde(skip)]
     pub error: Option<ErrReport>,
@@ -28,8 +39,8 @@ impl StateEdit {
         egui::Window::new(if !selected.is_empty() {
             format!(
                 "Edit selected: {}..{}",
-                selected.iter().next().unwrap_or(&0),
-                selected.iter().last().unwrap_or(&0)
+                selected.iter().min().unwrap_or(&0),
+                selected.iter().max().unwrap_or(&0)
             )
         } else {
             format!("Edit: {}", self.index)
@@ -49,30 +60,64 @@ impl StateEdit {
             }
             WaveType::Wire => {
                 let mut v = self.init_value.bool();
-                if !selected.is_empty(){
-                    if ui.checkbox(&mut v, "value").changed(){
+                let mut changed = false;
+                ui.horizontal(|ui| {
+                    if ui.checkbox(&mut v, "value").changed() {
                         self.init_value.neg_bool();
+                        changed = true;
                     };
+                    if ui.button("X").clicked() {
+                        self.init_value.set_unknown();
+                        changed = true;
+                    }
+                    if ui.button("Z").clicked() {
+                        self.init_value.set_high_z();
+                        changed = true;
+                    }
+                });
+                if !selected.is_empty(){
                     if ui.button("Save").clicked(){
                         state = WindowResult::Save;
                     }
-                } else if ui.checkbox(&mut v, "value").changed() {
-                    self.init_value.neg_bool();
+                } else if changed {
                     state = WindowResult::Save;
                 }
                 
             }
-            WaveType::Reg(_) => {
-                if let Some(v) = &mut self.current_value {
+            WaveType::Reg(size) => {
+                if let (WaveDisplay::Enum, Some(table)) = (self.display, &self.enum_table) {
+                    egui::ComboBox::new("enum_state", "State")
+                        .selected_text(table.print(&self.init_value))
+                        .show_ui(ui, |ui| {
+                            for s in &table.states {
+                                let selected = table.name(&self.init_value) == Some(&s.name);
+                                if ui.selectable_label(selected, &s.name).clicked() {
+                                    self.init_value.assign(BitValue::from_u64(size, s.value));
+                                }
+                            }
+                        });
+                    if ui.button("Save").clicked() {
+                        state = WindowResult::Save;
+                    };
+                } else if let Some(v) = &mut self.current_value {
                     if ui.text_edit_singleline(v).changed() {
-                        if let Err(e) = self.init_value.parse_from(v) {
+                        let parsed = parse_sample(
+                            &mut self.init_value,
+                            v,
+                            self.display,
+                            self.fixed.as_ref(),
+                        );
+                        if let Err(e) = parsed {
                             self.error = Some(e.with_source_code(v.clone()));
                         } else {
                             self.error = None;
                         };
                     }
                     if let Some(e) = &self.error {
-                        ui.label(format!("{}", e));
+                        let mut report = String::new();
+                        let _ =
+                            NarratableReportHandler::new().render_report(&mut report, e.as_ref());
+                        ui.label(report);
                         ui.set_enabled(false);
                     } else {
                         ui.set_enabled(true);
@@ -81,12 +126,11 @@ impl StateEdit {
                         state = WindowResult::Save;
                     };
                 } else {
-                    self.current_value = Some(match self.display {
-                        WaveDisplay::Binary => self.init_value.to_bin(),
-                        WaveDisplay::Hex => self.init_value.to_hex(),
-                        WaveDisplay::Decimal(s) => self.init_value.to_dec(s.signed()),
-                        WaveDisplay::Analog(s) => self.init_value.to_dec(s.signed()),
-                    });
+                    self.current_value = Some(print_sample(
+                        &self.init_value,
+                        self.display,
+                        self.fixed.as_ref(),
+                    ));
                 }
             }
         });
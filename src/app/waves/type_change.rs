@@ -2,15 +2,24 @@ use egui::Ui;
 use log::error;
 use serde::{Deserialize, Serialize};
 
-use crate::{app::windows::WindowResult, hseparator};
+use crate::{
+    app::{time::TimeScale, windows::WindowResult},
+    hseparator,
+};
 
-use super::{value::BitValue, wtype::WaveType};
+use super::{value::BitValue, wtype::WaveType, WaveId};
 
 #[derive(Serialize, Deserialize, Clone)]
 pub(super) struct TypeChange {
     pub current_tp: WaveType,
     pub new_tp: WaveType,
     pub max_size: usize,
+    /// Clock is edited in time units
+    #[serde(default)]
+    pub scale: TimeScale,
+    /// Waves able to enable clock
+    #[serde(skip)]
+    pub gates: Vec<(WaveId, String)>,
 }
 
 impl TypeChange {
@@ -39,19 +48,49 @@ impl TypeChange {
         state
     }
     fn display_clock(&mut self, ui: &mut Ui) {
+        let scale = self.scale;
         if let WaveType::Clock(c) = &mut self.new_tp {
             ui.horizontal(|ui| {
                 ui.label("Period");
-                ui.add(egui::DragValue::new(&mut c.period).clamp_range(0..=self.max_size));
+                ui.add(time_value(&mut c.period, scale).clamp_range(1..=self.max_size));
             });
             ui.horizontal(|ui| {
-                ui.label("Period");
-                ui.add(egui::DragValue::new(&mut c.duty).clamp_range(0..=c.period));
+                ui.label("Duty");
+                let period = c.period;
+                ui.add(time_value(&mut c.duty, scale).clamp_range(0..=period));
             });
             ui.horizontal(|ui| {
                 ui.label("Phase");
-                ui.add(egui::DragValue::new(&mut c.phase).clamp_range(0..=c.period));
+                let period = c.period;
+                ui.add(time_value(&mut c.phase, scale).clamp_range(0..=period));
+            });
+            ui.horizontal(|ui| {
+                ui.label("Start");
+                ui.add(time_value(&mut c.start, scale).clamp_range(0..=self.max_size));
+            });
+            ui.horizontal(|ui| {
+                let mut stops = c.stop.is_some();
+                ui.checkbox(&mut stops, "Stop");
+                let mut stop = c.stop.unwrap_or(self.max_size);
+                let start = c.start;
+                let value = time_value(&mut stop, scale).clamp_range(start..=self.max_size);
+                ui.add_enabled(stops, value);
+                c.stop = stops.then_some(stop);
             });
+            let gate = c.gate.map(|g| {
+                let name = self.gates.iter().find(|(id, _)| *id == g);
+                name.map_or("Missing wave", |(_, name)| name.as_str())
+            });
+            egui::ComboBox::new("clock_gate", "Enable")
+                .selected_text(gate.unwrap_or("Always"))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut c.gate, None, "Always");
+                    for (id, name) in &self.gates {
+                        ui.selectable_value(&mut c.gate, Some(*id), name);
+                    }
+                });
+            ui.checkbox(&mut c.differential, "Differential pair");
+            ui.label(egui::RichText::new(format!("Cycle is {}", scale.format(1.0))).small());
         };
     }
 
@@ -68,3 +107,10 @@ impl TypeChange {
         };
     }
 }
+
+/// Value is kept in cycles, shown and typed as time
+fn time_value(v: &mut usize, scale: TimeScale) -> egui::DragValue<'_> {
+    egui::DragValue::new(v)
+        .custom_formatter(move |n, _| scale.format(n))
+        .custom_parser(move |s| scale.parse(s).map(f64::round))
+}
@@ -0,0 +1,214 @@
+//! Stored form of values. Value is width prefixed hex `8hff`, binary `4b10xz` if some hex digit
+//! has mixed `x`/`z` bits. Quote of verilog literal is dropped, RON escapes it.
+//! Samples of wave are run-length encoded: repeated value is `(count, "1b0")`.
+//! Old projects with struct of words per value still load.
+
+use std::fmt;
+
+use serde::{
+    de::{self, value::MapAccessDeserializer, MapAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use super::{words::Words, BitValue};
+
+/// Old stored layout. Magnitude with `neg` flag, before 0.2 there are no `x`/`z` masks
+#[derive(Deserialize)]
+struct BitValueRepr {
+    bits_size: usize,
+    data: Words,
+    #[serde(default)]
+    unknown: Words,
+    #[serde(default)]
+    high_z: Words,
+    #[serde(default)]
+    neg: bool,
+    lsb: bool,
+}
+
+impl From<BitValueRepr> for BitValue {
+    fn from(r: BitValueRepr) -> Self {
+        let v = BitValue {
+            bits_size: r.bits_size,
+            data: r.data,
+            unknown: r.unknown,
+            high_z: r.high_z,
+            lsb: r.lsb,
+        };
+        if r.neg {
+            -v
+        } else {
+            v
+        }
+    }
+}
+
+impl BitValue {
+    fn encode(&self) -> String {
+        self.to_literal().replacen('\'', "", 1)
+    }
+
+    fn decode(s: &str) -> Result<Self, String> {
+        let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (size, digits) = s.split_at(split);
+        let size = match size.parse::<usize>() {
+            Ok(size) if digits.starts_with(['h', 'b']) => size,
+            _ => return Err(format!("Value `{}` must be like 8hff or 4b10xz", s)),
+        };
+        let mut v = BitValue::new(size);
+        if size > 0 {
+            v.parse_from(&format!("{}'{}", size, digits))
+                .map_err(|e| format!("Value `{}`: {}", s, e))?;
+        }
+        Ok(v)
+    }
+}
+
+impl Serialize for BitValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.encode())
+    }
+}
+
+impl<'de> Deserialize<'de> for BitValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ValueVisitor;
+
+        impl<'de> Visitor<'de> for ValueVisitor {
+            type Value = BitValue;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("sized verilog literal or struct of value words")
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<BitValue, E> {
+                BitValue::decode(s).map_err(E::custom)
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<BitValue, A::Error> {
+                BitValueRepr::deserialize(MapAccessDeserializer::new(map)).map(Into::into)
+            }
+        }
+
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum Run {
+    Repeat(usize, BitValue),
+    Single(BitValue),
+}
+
+/// Run-length encoding of samples for `#[serde(with = "...")]`
+pub mod rle {
+    use serde::{ser::SerializeSeq, Deserialize, Deserializer, Serializer};
+
+    use super::{BitValue, Run};
+
+    pub fn serialize<S: Serializer>(data: &[BitValue], serializer: S) -> Result<S::Ok, S::Error> {
+        let mut runs: Vec<(usize, &BitValue)> = Vec::new();
+        for v in data {
+            match runs.last_mut() {
+                Some((n, last)) if last.bits_size == v.bits_size && *last == v => *n += 1,
+                _ => runs.push((1, v)),
+            }
+        }
+        let mut seq = serializer.serialize_seq(Some(runs.len()))?;
+        for (n, v) in runs {
+            if n == 1 {
+                seq.serialize_element(v)?;
+            } else {
+                seq.serialize_element(&(n, v))?;
+            }
+        }
+        seq.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<BitValue>, D::Error> {
+        let mut data = Vec::new();
+        for run in Vec::<Run>::deserialize(deserializer)? {
+            match run {
+                Run::Repeat(n, v) => data.resize(data.len() + n, v),
+                Run::Single(v) => data.push(v),
+            }
+        }
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde::{Deserialize, Serialize};
+
+    use super::BitValue;
+    use crate::app::waves::value::Logic;
+
+    #[derive(Serialize, Deserialize)]
+    struct Samples {
+        #[serde(with = "super::rle")]
+        data: Vec<BitValue>,
+    }
+
+    fn bv(size: usize, s: &str) -> BitValue {
+        let mut v = BitValue::new(size);
+        v.parse_from(s).unwrap();
+        v
+    }
+
+    #[test]
+    fn test_value() {
+        assert_eq!(ron::to_string(&bv(8, "8'hff")).unwrap(), "\"8hff\"");
+        assert_eq!(ron::to_string(&bv(6, "6'bxxzz01")).unwrap(), "\"6bxxzz01\"");
+        assert_eq!(ron::to_string(&bv(8, "8'hxz")).unwrap(), "\"8hxz\"");
+        assert_eq!(ron::to_string(&BitValue::new(0)).unwrap(), "\"0h\"");
+        for v in [
+            bv(8, "-1"),
+            bv(6, "6'bxxzz01"),
+            bv(200, "200'hz1x"),
+            BitValue::new(0),
+        ] {
+            let s = ron::to_string(&v).unwrap();
+            let nv: BitValue = ron::from_str(&s).unwrap();
+            assert_eq!(nv.bits_size, v.bits_size);
+            assert_eq!(nv.to_bin(), v.to_bin());
+        }
+        assert!(ron::from_str::<BitValue>("\"8h1ff\"").is_err());
+        assert!(ron::from_str::<BitValue>("\"hff\"").is_err());
+        assert!(ron::from_str::<BitValue>("\"8'hff\"").is_err());
+    }
+
+    #[test]
+    fn test_old_layout() {
+        let old = "(bits_size:8,data:(5,0,0,0,0,0,0,0),neg:true,lsb:true)";
+        let v: BitValue = ron::from_str(old).unwrap();
+        assert_eq!(v.to_dec(true), "-00000005");
+        let old = "(data:[(bits_size:1,data:(1,0,0,0,0,0,0,0),neg:false,lsb:true),\
+            (bits_size:1,data:(0,0,0,0,0,0,0,0),neg:false,lsb:true)])";
+        let s: Samples = ron::from_str(old).unwrap();
+        assert_eq!(s.data.len(), 2);
+        assert_eq!(s.data[0].to_bin(), "1");
+    }
+
+    #[test]
+    fn test_rle() {
+        let mut data = vec![bv(1, "1"); 100_000];
+        data[10].set_bit(0, Logic::V0);
+        data.push(bv(2, "2'b01"));
+        data.push(bv(1, "1'bz"));
+        let s = ron::to_string(&Samples { data: data.clone() }).unwrap();
+        assert_eq!(
+            s,
+            "(data:[(10,\"1h1\"),\"1h0\",(99989,\"1h1\"),\"2h1\",\"1hz\"])"
+        );
+        let nd = ron::from_str::<Samples>(&s).unwrap().data;
+        assert_eq!(nd.len(), data.len());
+        assert!(nd
+            .iter()
+            .zip(&data)
+            .all(|(a, b)| a.bits_size == b.bits_size && a == b));
+    }
+}
@@ -0,0 +1,175 @@
+//! IEEE-754 interpretation of [`BitValue`]: half precision for 16 bits,
+//! `shortreal` for 32 bits and `real` for 64 bits.
+
+use miette::{ErrReport, LabeledSpan, MietteDiagnostic};
+
+use super::BitValue;
+
+impl BitValue {
+    /// Width has IEEE-754 format
+    pub fn is_float_size(size: usize) -> bool {
+        matches!(size, 16 | 32 | 64)
+    }
+
+    /// Decoded float. `None` for width without float format or if any `x`/`z` bit
+    pub fn to_float(&self) -> Option<f64> {
+        if !self.is_known() {
+            return None;
+        }
+        let bits = self.word(0);
+        match self.bits_size {
+            16 => Some(f16_to_f64(bits as u16)),
+            32 => Some(f32::from_bits(bits as u32) as f64),
+            64 => Some(f64::from_bits(bits)),
+            _ => None,
+        }
+    }
+
+    /// Nearest float of `size` width, `None` for width without float format
+    pub fn from_float(size: usize, v: f64) -> Option<Self> {
+        let bits = match size {
+            16 => f64_to_f16(v) as u64,
+            32 => (v as f32).to_bits() as u64,
+            64 => v.to_bits(),
+            _ => return None,
+        };
+        Some(BitValue::from_u64(size, bits))
+    }
+
+    /// Parse float number like `3.14`, `-1e-3` or `inf` and encode it into bits.
+    /// Anything else parsed as raw bits by [`BitValue::parse_from`]
+    pub fn parse_float(&mut self, s: &str) -> Result<(), ErrReport> {
+        let Ok(v) = s.trim().parse::<f64>() else {
+            return self.parse_from(s);
+        };
+        match BitValue::from_float(self.bits_size, v) {
+            Some(mut value) => {
+                value.lsb = self.lsb;
+                *self = value;
+                Ok(())
+            }
+            None => Err(ErrReport::new(
+                MietteDiagnostic::new("Error by parsing value")
+                    .with_code(s)
+                    .with_severity(miette::Severity::Error)
+                    .with_help("Float values can be stored only in 16, 32 or 64 bits")
+                    .with_label(LabeledSpan::at(
+                        (0, s.len()),
+                        format!("{} bits has no float format", self.bits_size),
+                    )),
+            )),
+        }
+    }
+}
+
+/// Half precision bits to value
+fn f16_to_f64(h: u16) -> f64 {
+    let sign = if h & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exp = (h >> 10) & 0x1f;
+    let man = (h & 0x3ff) as f64;
+    sign * match exp {
+        0 => man * 2f64.powi(-24),
+        0x1f if man == 0.0 => f64::INFINITY,
+        0x1f => f64::NAN,
+        e => (1024.0 + man) * 2f64.powi(e as i32 - 25),
+    }
+}
+
+/// Nearest half precision bits, ties to even
+fn f64_to_f16(v: f64) -> u16 {
+    let b = v.to_bits();
+    let sign = ((b >> 48) & 0x8000) as u16;
+    let exp = ((b >> 52) & 0x7ff) as i32;
+    let man = b & ((1 << 52) - 1);
+    if exp == 0x7ff {
+        return sign | 0x7c00 | if man != 0 { 0x200 } else { 0 };
+    }
+    let e = exp - 1023 + 15;
+    if e >= 0x1f {
+        return sign | 0x7c00;
+    }
+    if e <= 0 {
+        if e < -10 {
+            return sign;
+        }
+        // Subnormal, carry to exponent gives smallest normal
+        return sign | round_shift(man | 1 << 52, (43 - e) as u32) as u16;
+    }
+    // Carry of mantissa to exponent is correct rounding, up to infinity
+    sign | (((e as u64) << 10) + round_shift(man, 42)) as u16
+}
+
+/// `m >> shift` rounded to nearest, ties to even
+fn round_shift(m: u64, shift: u32) -> u64 {
+    let q = m >> shift;
+    let rem = m & ((1 << shift) - 1);
+    let half = 1 << (shift - 1);
+    if rem > half || (rem == half && q & 0b1 == 1) {
+        q + 1
+    } else {
+        q
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{f16_to_f64, f64_to_f16, BitValue};
+
+    #[test]
+    fn test_half() {
+        let cases = [
+            (0x0000, 0.0),
+            (0x3c00, 1.0),
+            (0xc000, -2.0),
+            (0x3555, 0.333251953125),
+            (0x7bff, 65504.0),
+            (0x0400, 6.103515625e-5),
+            (0x0001, 5.960464477539063e-8),
+            (0x03ff, 6.097555160522461e-5),
+            (0x7c00, f64::INFINITY),
+            (0xfc00, f64::NEG_INFINITY),
+        ];
+        for (h, v) in cases {
+            assert_eq!(f16_to_f64(h), v);
+            assert_eq!(f64_to_f16(v), h);
+        }
+        assert!(f16_to_f64(0x7e00).is_nan());
+        assert_eq!(f64_to_f16(f64::NAN) & 0x7e00, 0x7e00);
+        assert_eq!(f64_to_f16(-0.0), 0x8000);
+        // Rounding: ties to even, overflow to infinity, underflow to zero
+        assert_eq!(f64_to_f16(1.0 + 2f64.powi(-11)), 0x3c00);
+        assert_eq!(f64_to_f16(1.0 + 3.0 * 2f64.powi(-11)), 0x3c02);
+        assert_eq!(f64_to_f16(65520.0), 0x7c00);
+        assert_eq!(f64_to_f16(65519.0), 0x7bff);
+        assert_eq!(f64_to_f16(2f64.powi(-25)), 0x0000);
+        assert_eq!(f64_to_f16(2f64.powi(-25) * 1.5), 0x0001);
+        assert_eq!(f64_to_f16(6.1e-5), 0x03ff);
+        for h in 0..0x7c00u16 {
+            assert_eq!(f64_to_f16(f16_to_f64(h)), h);
+        }
+    }
+
+    #[test]
+    fn test_float_value() {
+        let mut bv = BitValue::new(32);
+        bv.parse_float("0.1").unwrap();
+        assert_eq!(bv.to_hex(), format!("{:08x}", 0.1f32.to_bits()));
+        assert_eq!(bv.to_float(), Some(0.1f32 as f64));
+        bv.parse_float("32'h3f800000").unwrap();
+        assert_eq!(bv.to_float(), Some(1.0));
+
+        let mut bv = BitValue::new(64);
+        bv.parse_float("-1e-3").unwrap();
+        assert_eq!(bv.to_hex(), format!("{:016x}", (-1e-3f64).to_bits()));
+        assert_eq!(bv.to_float(), Some(-1e-3));
+
+        let mut bv = BitValue::new(16);
+        bv.parse_float("inf").unwrap();
+        assert_eq!(bv.to_hex(), "7c00");
+        bv.parse_float("16'hxxxx").unwrap();
+        assert_eq!(bv.to_float(), None);
+
+        assert!(BitValue::new(8).parse_float("1.5").is_err());
+        assert_eq!(BitValue::new(8).to_float(), None);
+    }
+}
@@ -0,0 +1,1177 @@
+mod encoding;
+mod float;
+mod ops;
+mod text;
+mod words;
+
+use std::{
+    fmt,
+    ops::{Index, IndexMut},
+};
+
+pub use self::encoding::rle;
+use self::words::Words;
+use miette::{ErrReport, LabeledSpan, MietteDiagnostic};
+
+/// Error of value parsing: byte span in source string and label for it
+struct ParseError {
+    span: (usize, usize),
+    label: String,
+}
+
+impl ParseError {
+    fn new(offset: usize, len: usize, label: impl Into<String>) -> Self {
+        ParseError {
+            span: (offset, len),
+            label: label.into(),
+        }
+    }
+
+    /// Error at single char with its byte offset
+    fn at(c: (usize, char), label: impl Into<String>) -> Self {
+        ParseError::new(c.0, c.1.len_utf8(), label)
+    }
+}
+
+/// Verilog four-state level of a single bit.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Logic {
+    V0,
+    V1,
+    X,
+    Z,
+}
+
+/// Verilog vector value. Negative values are kept in two's complement of `bits_size` width.
+#[derive(Clone)]
+pub struct BitValue {
+    bits_size: usize,
+    data: Words,
+    /// Bits in unknown (`x`) state. Value bit under mask always 0.
+    unknown: Words,
+    /// Bits in high impedance (`z`) state. Value bit under mask always 0.
+    high_z: Words,
+    lsb: bool,
+}
+
+#[derive(PartialEq, Eq)]
+enum IntBase {
+    B2,
+    B8,
+    B10,
+    B16,
+}
+
+impl IntBase {
+    fn get_size(&self) -> usize {
+        match self {
+            IntBase::B2 => 64,
+            IntBase::B8 => 21,
+            IntBase::B10 => 19,
+            IntBase::B16 => 16,
+        }
+    }
+    fn get_radix(&self) -> u32 {
+        match self {
+            IntBase::B2 => 2,
+            IntBase::B8 => 8,
+            IntBase::B10 => 10,
+            IntBase::B16 => 16,
+        }
+    }
+
+    /// Bits in one digit. Only for power of two bases
+    fn digit_bits(&self) -> usize {
+        match self {
+            IntBase::B2 => 1,
+            IntBase::B8 => 3,
+            IntBase::B10 => 0,
+            IntBase::B16 => 4,
+        }
+    }
+
+    /// Biggest power of radix that fit in u64: radix^get_size
+    fn chunk(&self) -> u64 {
+        (self.get_radix() as u64).pow(self.get_size() as u32)
+    }
+
+    fn print(&self, v: u64, bits: usize) -> String {
+        match self {
+            IntBase::B2 => format!("{:0>1$b}", v, bits),
+            IntBase::B8 => format!("{:0>1$o}", v, bits),
+            IntBase::B10 => format!("{:0>1$}", v, bits),
+            IntBase::B16 => format!("{:0>1$x}", v, bits),
+        }
+    }
+}
+
+impl BitValue {
+    pub const BYTE: usize = Words::BITS;
+    /// Max vector width, same as verilog tools guarantee
+    pub const BITS: usize = 1 << 16;
+
+    pub fn new(size: usize) -> Self {
+        BitValue {
+            bits_size: size,
+            data: Words::default(),
+            unknown: Words::default(),
+            high_z: Words::default(),
+            lsb: true,
+        }
+    }
+
+    /// Parse value in verilog number syntax: `8'hFF`, `'d42`, `16'sb1111_0000`, `4'bx`, `'1`.
+    /// `0b`, `0o`, `0x` prefixes and plain decimal also accepted. Size of sized literal must be
+    /// equal to value size
+    pub fn parse_from(&mut self, s: &str) -> Result<(), ErrReport> {
+        match self.parse_literal(s) {
+            Ok(value) => {
+                *self = value;
+                Ok(())
+            }
+            Err(e) => Err(ErrReport::new(
+                MietteDiagnostic::new("Error by parsing value")
+                    .with_code(s)
+                    .with_severity(miette::Severity::Error)
+                    .with_help(
+                        "Value must be verilog literal like 8'hff, 'd42, 4'bx, '1 \
+                        or start from -+ 0b, 0o, 0x or be decimal. And size must be enough",
+                    )
+                    .with_label(LabeledSpan::at(e.span, e.label)),
+            )),
+        }
+    }
+
+    /// Value of sized literal keeps own width, anything else parsed into `size` bits
+    pub fn from_literal(s: &str, size: usize) -> Result<Self, ErrReport> {
+        let width = s
+            .split_once('\'')
+            .and_then(|(w, _)| w.trim().parse::<usize>().ok())
+            .filter(|w| (1..=Self::BITS).contains(w))
+            .unwrap_or(size);
+        let mut v = BitValue::new(width);
+        v.parse_from(s)?;
+        Ok(v)
+    }
+
+    /// Shortest sized literal keeping all bits: hex, or binary if some hex digit has
+    /// mixed `x`/`z` bits
+    pub fn to_literal(&self) -> String {
+        let hex = self.to_hex();
+        if hex.contains(['X', 'Z']) {
+            format!("{}'b{}", self.bits_size, self.to_bin())
+        } else {
+            format!("{}'h{}", self.bits_size, hex)
+        }
+    }
+
+    fn parse_literal(&self, s: &str) -> Result<BitValue, ParseError> {
+        let chars: Vec<(usize, char)> = s.char_indices().collect();
+        let skip_ws = |i: &mut usize| {
+            while chars.get(*i).map_or(false, |(_, c)| c.is_whitespace()) {
+                *i += 1;
+            }
+        };
+        let take_word = |i: &mut usize| {
+            let first = *i;
+            while chars.get(*i).map_or(false, |(_, c)| {
+                c.is_alphanumeric() || *c == '_' || *c == '?'
+            }) {
+                *i += 1;
+            }
+            first..*i
+        };
+        let end = |i: usize| {
+            let mut i = i;
+            skip_ws(&mut i);
+            match chars.get(i) {
+                Some(c) => Err(ParseError::at(*c, "unexpected symbol")),
+                None => Ok(()),
+            }
+        };
+
+        let mut i = 0;
+        skip_ws(&mut i);
+        let neg = match chars.get(i) {
+            Some((_, c @ ('-' | '+'))) => {
+                i += 1;
+                *c == '-'
+            }
+            Some(_) => false,
+            None => return Err(ParseError::new(0, s.len(), "At least must be 1 symbol")),
+        };
+        skip_ws(&mut i);
+        let result = |value: BitValue| {
+            let mut value = if neg { -value } else { value };
+            value.lsb = self.lsb;
+            Ok(value)
+        };
+
+        let mut value = BitValue::new(self.bits_size);
+        let word = take_word(&mut i);
+        let mut quote = i;
+        skip_ws(&mut quote);
+        if !matches!(chars.get(quote), Some((_, '\''))) {
+            end(i)?;
+            let offset = chars.get(word.start).map_or(s.len(), |c| c.0);
+            match &chars[word] {
+                [(_, '0'), b, digits @ ..] if b.1.is_alphabetic() => {
+                    let base = match b.1.to_ascii_lowercase() {
+                        'b' => IntBase::B2,
+                        'o' => IntBase::B8,
+                        'x' => IntBase::B16,
+                        _ => {
+                            return Err(ParseError::at(
+                                *b,
+                                "expected base type: 0b, -0o, 0x, 99. Aplabetic found here",
+                            ))
+                        }
+                    };
+                    value.parse_digits(&Self::digits(digits)?, offset + 2, &base)?;
+                }
+                digits => value.parse_decimal(&Self::digits(digits)?, offset)?,
+            }
+            return result(value);
+        }
+
+        let sized = !word.is_empty();
+        if sized {
+            let size = &chars[word];
+            let last = size[size.len() - 1];
+            let span = (size[0].0, last.0 + last.1.len_utf8() - size[0].0);
+            let digits = Self::digits(size)?;
+            let mut n = 0usize;
+            for c in &digits {
+                let Some(d) = c.1.to_digit(10) else {
+                    return Err(ParseError::at(*c, "invalid digit in size"));
+                };
+                n = n.saturating_mul(10).saturating_add(d as usize);
+            }
+            if n == 0 {
+                return Err(ParseError::new(span.0, span.1, "size must be positive"));
+            }
+            if n != self.bits_size {
+                return Err(ParseError::new(
+                    span.0,
+                    span.1,
+                    format!("size {} don't match wave width {}", n, self.bits_size),
+                ));
+            }
+        }
+
+        i = quote + 1;
+        let signed = matches!(chars.get(i), Some((_, 's' | 'S')));
+        if signed {
+            i += 1;
+        }
+        let Some(b) = chars.get(i).copied() else {
+            return Err(ParseError::new(s.len(), 0, "expected base: b, o, d or h"));
+        };
+        i += 1;
+        let base = match b.1.to_ascii_lowercase() {
+            'b' => IntBase::B2,
+            'o' => IntBase::B8,
+            'd' => IntBase::B10,
+            'h' => IntBase::B16,
+            c @ ('0' | '1' | 'x' | 'z' | '?') if !sized && !signed => {
+                end(i)?;
+                match c {
+                    '0' => {}
+                    '1' => value = !&value,
+                    'x' => value.set_unknown(),
+                    _ => value.set_high_z(),
+                }
+                return result(value);
+            }
+            _ => return Err(ParseError::at(b, "expected base: b, o, d or h")),
+        };
+        skip_ws(&mut i);
+        let word = take_word(&mut i);
+        end(i)?;
+        let offset = chars.get(word.start).map_or(s.len(), |c| c.0);
+        let digits = Self::digits(&chars[word])?;
+        if base == IntBase::B10 {
+            value.parse_decimal(&digits, offset)?;
+        } else {
+            value.parse_digits(&digits, offset, &base)?;
+        }
+        result(value)
+    }
+
+    /// Digits without `_` separators. Separator can't be first
+    fn digits(word: &[(usize, char)]) -> Result<Vec<(usize, char)>, ParseError> {
+        if let Some(c) = word.first().filter(|c| c.1 == '_') {
+            return Err(ParseError::at(*c, "number can't start from _"));
+        }
+        Ok(word.iter().filter(|c| c.1 != '_').copied().collect())
+    }
+
+    /// Decimal digits or single `x`/`z` for whole value.
+    /// `offset` is position of digits in source string, used for empty digits.
+    fn parse_decimal(&mut self, digits: &[(usize, char)], offset: usize) -> Result<(), ParseError> {
+        match digits {
+            [] => {
+                return Err(ParseError::new(
+                    offset,
+                    0,
+                    "cannot parse integer from empty string",
+                ))
+            }
+            [(_, c)] if c.eq_ignore_ascii_case(&'x') => {
+                self.set_unknown();
+                return Ok(());
+            }
+            [(_, c)] if c.eq_ignore_ascii_case(&'z') || *c == '?' => {
+                self.set_high_z();
+                return Ok(());
+            }
+            _ => {}
+        }
+        let words = Words::count(self.bits_size);
+        for c in digits {
+            let Some(d) = c.1.to_digit(10) else {
+                return Err(ParseError::at(*c, "invalid digit found in string"));
+            };
+            let mut carry = d as u128;
+            for i in 0..words {
+                let cur = self.data.get(i) as u128 * 10 + carry;
+                self.data.set(i, cur as u64);
+                carry = cur >> 64;
+            }
+            let over = (0..words).any(|i| self.data.get(i) & !Words::mask(self.bits_size, i) != 0);
+            if carry != 0 || over {
+                return Err(ParseError::at(*c, "to long value"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Fill bits from digits of power of two base, `x`/`z` digits fill all digit bits.
+    /// Leftmost `x`/`z` digit extends to all upper bits, like in verilog.
+    /// `offset` is position of digits in source string, used for empty digits.
+    fn parse_digits(
+        &mut self,
+        digits: &[(usize, char)],
+        offset: usize,
+        base: &IntBase,
+    ) -> Result<(), ParseError> {
+        let state = |c: char| match c.to_ascii_lowercase() {
+            'x' => Some(Logic::X),
+            'z' | '?' => Some(Logic::Z),
+            _ => None,
+        };
+        let Some(first) = digits.first() else {
+            return Err(ParseError::new(
+                offset,
+                0,
+                "cannot parse integer from empty string",
+            ));
+        };
+        let step = base.digit_bits();
+        if let Some(state) = state(first.1) {
+            for b in digits.len() * step..self.bits_size {
+                self.set_bit(b, state);
+            }
+        }
+        for (n, c) in digits.iter().rev().enumerate() {
+            let first = n * step;
+            if let Some(state) = state(c.1) {
+                for b in first..(first + step).min(self.bits_size) {
+                    self.set_bit(b, state);
+                }
+                continue;
+            }
+            let Some(d) = c.1.to_digit(base.get_radix()) else {
+                return Err(ParseError::at(*c, "invalid digit found in string"));
+            };
+            for b in 0..step {
+                if d >> b & 0b1 == 0 {
+                    continue;
+                }
+                if first + b >= self.bits_size {
+                    return Err(ParseError::at(*c, "to long value"));
+                }
+                self.set_bit(first + b, Logic::V1);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn set_zero(&mut self) {
+        self.data.clear();
+        self.unknown.clear();
+        self.high_z.clear();
+    }
+
+    pub fn set_bool(&mut self, v: bool) {
+        self.set_zero();
+        self.data.set(0, v as u64);
+    }
+
+    /// Set all bits to `x`
+    pub fn set_unknown(&mut self) {
+        self.set_zero();
+        for i in 0..Words::count(self.bits_size) {
+            self.unknown.set(i, Words::mask(self.bits_size, i));
+        }
+    }
+
+    /// Set all bits to `z`
+    pub fn set_high_z(&mut self) {
+        self.set_zero();
+        for i in 0..Words::count(self.bits_size) {
+            self.high_z.set(i, Words::mask(self.bits_size, i));
+        }
+    }
+
+    pub fn get_bit(&self, bit: usize) -> Logic {
+        let (i, m) = (bit / Self::BYTE, 1u64 << (bit % Self::BYTE));
+        if self.high_z.get(i) & m != 0 {
+            Logic::Z
+        } else if self.unknown.get(i) & m != 0 {
+            Logic::X
+        } else if self.data.get(i) & m != 0 {
+            Logic::V1
+        } else {
+            Logic::V0
+        }
+    }
+
+    pub fn set_bit(&mut self, bit: usize, v: Logic) {
+        let (i, m) = (bit / Self::BYTE, 1u64 << (bit % Self::BYTE));
+        self.data.update(i, |w| w & !m);
+        self.unknown.update(i, |w| w & !m);
+        self.high_z.update(i, |w| w & !m);
+        match v {
+            Logic::V0 => {}
+            Logic::V1 => self.data.update(i, |w| w | m),
+            Logic::X => self.unknown.update(i, |w| w | m),
+            Logic::Z => self.high_z.update(i, |w| w | m),
+        }
+    }
+
+    /// At least one bit is `x`
+    pub fn has_unknown(&self) -> bool {
+        !self.unknown.is_zero()
+    }
+
+    /// At least one bit is `z`
+    pub fn has_high_z(&self) -> bool {
+        !self.high_z.is_zero()
+    }
+
+    /// All bits is `z`
+    pub fn is_high_z(&self) -> bool {
+        Self::is_full(&self.high_z, self.bits_size)
+    }
+
+    fn is_full(mask: &Words, bits: usize) -> bool {
+        (0..Words::count(bits)).all(|i| mask.get(i) == Words::mask(bits, i))
+    }
+
+    pub fn size(&self) -> usize {
+        self.bits_size
+    }
+
+    pub fn set_size(&mut self, size: usize) -> Result<(), ()> {
+        if size > Self::BITS {
+            return Err(());
+        };
+        if size < self.bits_size {
+            self.data.truncate(size);
+            self.unknown.truncate(size);
+            self.high_z.truncate(size);
+        }
+        self.bits_size = size;
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn bool(&self) -> bool {
+        (self.data.get(0) & 0b1) as u8 != 0
+    }
+
+    /// Edit self bool = !bool. `x` and `z` become `1`
+    #[inline(always)]
+    pub fn neg_bool(&mut self) {
+        self.unknown.update(0, |w| w & !0b1);
+        self.high_z.update(0, |w| w & !0b1);
+        self.data.update(0, |w| !w & 0b1);
+    }
+
+    pub fn to_bin(&self) -> String {
+        self.print_digits(IntBase::B2)
+    }
+
+    #[allow(unused)]
+    pub fn to_oct(&self) -> String {
+        self.print_digits(IntBase::B8)
+    }
+
+    /// Like verilog `%d`: `x`/`z` if all bits unknown/high impedance, `X`/`Z` if only some.
+    /// Known value is zero padded to [`BitValue::dec_width`]
+    pub fn to_dec(&self, signed: bool) -> String {
+        if self.has_unknown() {
+            if Self::is_full(&self.unknown, self.bits_size) {
+                return "x".into();
+            }
+            return "X".into();
+        }
+        if self.has_high_z() {
+            if self.is_high_z() {
+                return "z".into();
+            }
+            return "Z".into();
+        }
+        let negative = signed && self.msb() == Logic::V1;
+        let digits = self.print_dec(negative);
+        let sign = if negative { "-" } else { "" };
+        format!("{}{:0>width$}", sign, digits, width = self.dec_width())
+    }
+
+    /// Digits of decimal: bits of top word or 21 for full top word, 19 for every lower word
+    fn dec_width(&self) -> usize {
+        let top = match self.bits_size % Self::BYTE {
+            0 => 21,
+            bits => bits,
+        };
+        top + IntBase::B10.get_size() * (Words::count(self.bits_size).max(1) - 1)
+    }
+
+    pub fn to_hex(&self) -> String {
+        self.print_digits(IntBase::B16)
+    }
+
+    pub fn from_u128(size: usize, v: u128) -> Self {
+        let mut r = BitValue::new(size);
+        r.data.set(0, v as u64);
+        r.data.set(1, (v >> 64) as u64);
+        r.data.truncate(size);
+        r
+    }
+
+    /// Two's complement of `v`, sign extended or truncated to `size`
+    pub fn from_i128(size: usize, v: i128) -> Self {
+        BitValue::from_u128(128, v as u128).sign_extend(size)
+    }
+
+    /// Two's complement of integer part of `v`, truncated to `size`. `None` for not finite `v`
+    pub fn from_f64(size: usize, v: f64) -> Option<Self> {
+        if !v.is_finite() {
+            return None;
+        }
+        let v = v.trunc();
+        if v == 0.0 {
+            return Some(BitValue::new(size));
+        }
+        // |v| = m * 2^exp with 53 bits m
+        let bits = v.abs().to_bits();
+        let exp = (bits >> 52) as i64 - 1075;
+        let m = (bits & ((1 << 52) - 1)) | 1 << 52;
+        let magnitude = if exp < 0 {
+            BitValue::from_u64(size, m >> -exp)
+        } else {
+            &BitValue::from_u64(size, m) << exp as usize
+        };
+        Some(if v < 0.0 { -magnitude } else { magnitude })
+    }
+
+    /// Take bits of `v` and keep own bit order
+    pub fn assign(&mut self, v: BitValue) {
+        let lsb = self.lsb;
+        *self = v;
+        self.lsb = lsb;
+    }
+
+    /// Low 128 bits, `x` and `z` bits counted as 0
+    fn low_u128(&self) -> u128 {
+        self.word(0) as u128 | (self.word(1) as u128) << 64
+    }
+
+    /// `None` if any `x`/`z` bit or value don't fit
+    pub fn to_u128(&self) -> Option<u128> {
+        let fit = (2..Words::count(self.bits_size)).all(|i| self.word(i) == 0);
+        (self.is_known() && fit).then(|| self.low_u128())
+    }
+
+    /// Value as two's complement. `None` if any `x`/`z` bit or value don't fit
+    pub fn to_i128(&self) -> Option<i128> {
+        if !self.is_known() {
+            return None;
+        }
+        if self.bits_size <= 128 {
+            return Some(sign_extend_i128(self.low_u128(), self.bits_size));
+        }
+        let v = self.low_u128() as i128;
+        (BitValue::from_i128(self.bits_size, v) == *self).then_some(v)
+    }
+
+    /// Nearest f64 to value. `x` and `z` bits counted as 0
+    pub fn to_f64(&self, signed: bool) -> f64 {
+        let size = self.bits_size;
+        let negative = signed
+            && size > 0
+            && (self.word((size - 1) / Self::BYTE) >> ((size - 1) % Self::BYTE)) & 0b1 != 0;
+        if size <= 128 {
+            let v = self.low_u128();
+            return if negative {
+                sign_extend_i128(v, size) as f64
+            } else {
+                v as f64
+            };
+        }
+        let mut words: Vec<u64> = (0..Words::count(size)).map(|i| self.word(i)).collect();
+        if negative {
+            let mut carry = true;
+            for (i, w) in words.iter_mut().enumerate() {
+                let (v, c) = (!*w & Words::mask(size, i)).overflowing_add(carry as u64);
+                *w = v;
+                carry = c;
+            }
+            -words_to_f64(&words)
+        } else {
+            words_to_f64(&words)
+        }
+    }
+
+    /// Print power of two base. Digit with all `x`/`z` bits printed as `x`/`z`,
+    /// with some of them as `X`/`Z`
+    fn print_digits(&self, base: IntBase) -> String {
+        let step = base.digit_bits();
+        let count = (self.bits_size + step - 1) / step;
+        let mut s = String::with_capacity(count);
+        for d in (0..count).rev() {
+            let first = d * step;
+            let last = (first + step).min(self.bits_size);
+            let (mut v, mut x, mut z) = (0u32, 0, 0);
+            for b in first..last {
+                match self.get_bit(b) {
+                    Logic::V0 => {}
+                    Logic::V1 => v |= 1 << (b - first),
+                    Logic::X => x += 1,
+                    Logic::Z => z += 1,
+                }
+            }
+            s.push(match (x, z) {
+                (0, 0) => char::from_digit(v, base.get_radix()).unwrap_or('?'),
+                (x, _) if x == last - first => 'x',
+                (x, _) if x > 0 => 'X',
+                (_, z) if z == last - first => 'z',
+                _ => 'Z',
+            });
+        }
+        s
+    }
+
+    /// Decimal digits of magnitude (of two's complement when `negative`), by division of
+    /// words by 10^19
+    fn print_dec(&self, negative: bool) -> String {
+        let base = IntBase::B10;
+        let magnitude = if negative { -self } else { self.clone() };
+        let mut words: Vec<u64> = (0..Words::count(self.bits_size))
+            .map(|i| magnitude.word(i))
+            .collect();
+        let mut chunks = Vec::new();
+        while words.iter().any(|w| *w != 0) {
+            let mut rem = 0u128;
+            for w in words.iter_mut().rev() {
+                let cur = (rem << 64) | *w as u128;
+                *w = (cur / base.chunk() as u128) as u64;
+                rem = cur % base.chunk() as u128;
+            }
+            chunks.push(rem as u64);
+        }
+        let mut s = String::new();
+        match chunks.pop() {
+            Some(top) => s += &top.to_string(),
+            None => s += "0",
+        }
+        for c in chunks.iter().rev() {
+            s += &base.print(*c, base.get_size());
+        }
+        s
+    }
+}
+
+/// Sign extend low `size` bits of `v`
+fn sign_extend_i128(v: u128, size: usize) -> i128 {
+    match size {
+        0 => 0,
+        s if s >= 128 => v as i128,
+        s => ((v << (128 - s)) as i128) >> (128 - s),
+    }
+}
+
+/// Correctly rounded unsigned value of little endian words
+fn words_to_f64(words: &[u64]) -> f64 {
+    let Some(top) = words.iter().rposition(|w| *w != 0) else {
+        return 0.0;
+    };
+    let get = |i: usize| words.get(i).copied().unwrap_or(0);
+    if top < 2 {
+        return (get(0) as u128 | (get(1) as u128) << 64) as f64;
+    }
+    // Take 128 bits from highest set bit, lower bits only matter for rounding
+    let shift = (top + 1) * BitValue::BYTE - words[top].leading_zeros() as usize - 128;
+    let (q, r) = (shift / BitValue::BYTE, shift % BitValue::BYTE);
+    let word = |i: usize| {
+        if r == 0 {
+            get(q + i)
+        } else {
+            get(q + i) >> r | get(q + i + 1) << (BitValue::BYTE - r)
+        }
+    };
+    let mut window = word(0) as u128 | (word(1) as u128) << 64;
+    let sticky =
+        words[..q].iter().any(|w| *w != 0) || (r != 0 && get(q) << (BitValue::BYTE - r) != 0);
+    if sticky {
+        window |= 1;
+    }
+    window as f64 * 2f64.powi(shift as i32)
+}
+
+impl fmt::Debug for BitValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}'b{}", self.bits_size, self.to_bin())
+    }
+}
+
+impl Index<usize> for BitValue {
+    type Output = u64;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        if index >= Words::count(Self::BITS) {
+            panic!("Out of range index {index}. Max: {}", Self::BITS);
+        }
+        self.data.get_ref(index)
+    }
+}
+
+impl IndexMut<usize> for BitValue {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        if index >= Words::count(Self::BITS) {
+            panic!("Out of range index {index}. Max: {}", Self::BITS);
+        }
+        self.data.get_mut(index)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BitValue, Logic, Words};
+
+    #[test]
+    fn test_shr() {
+        let a = -0b1i64;
+        println!("{:b}", !(a << 4));
+        let b = !(-0b1i64 << 4) as u64;
+        let c = 0b11_1111u64;
+        println!("{:b}", c & b);
+    }
+
+    #[test]
+    fn test_op_code() {
+        for i in 1..11 {
+            let a: i8 = i;
+            let b = !a + 1;
+            println!("-{} => {:b}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_saturating() {
+        println!("{}", 0u8.saturating_sub(1));
+        println!("{}", 0b1u8.ilog2());
+        println!("{}", 0b10u8.ilog2());
+        println!("{}", 0b11u8.ilog2());
+        println!("{}", 0b110u8.ilog2());
+        println!("{}", 0b111u8.ilog2());
+    }
+
+    #[test]
+    fn test_to_bin() {
+        let mut bv = BitValue::new(128);
+        let a = i64::MAX.to_le_bytes();
+        let mut rb = [0u8; 128 / 8];
+        for (i, av) in a.iter().enumerate() {
+            rb[i] = *av;
+        }
+        rb[9] = 0xff;
+        let b = i128::from_le_bytes(rb);
+        println!("{:b}", b);
+        bv.parse_from(&format!("0b{:b}", b)).unwrap();
+        assert_eq!(format!("{:0>128b}", b), format!("{}", bv.to_bin()));
+    }
+
+    #[test]
+    fn test_to_bin_neg() {
+        let mut bv = BitValue::new(128);
+        let a = i64::MAX.to_le_bytes();
+        let mut rb = [0u8; 128 / 8];
+        for (i, av) in a.iter().enumerate() {
+            rb[i] = *av;
+        }
+        rb[9] = 0xff;
+        let b = -i128::from_le_bytes(rb);
+        println!("{:x}", b);
+        bv.parse_from(&format!("0b{:b}", b)).unwrap();
+        println!("{:0>128b}", b);
+        println!("{}", bv.to_bin());
+        assert_eq!(format!("{:0>128b}", b), format!("{}", bv.to_bin()));
+    }
+
+    #[test]
+    fn test_to_oct() {
+        let mut bv = BitValue::new(128);
+        let a = i64::MAX.to_le_bytes();
+        let mut rb = [0u8; 128 / 8];
+        for (i, av) in a.iter().enumerate() {
+            rb[i] = *av;
+        }
+        rb[9] = 0xff;
+        let b = i128::from_le_bytes(rb);
+        println!("{:o}", b);
+
+        match bv.parse_from(&format!("0o{:o}vv", b)) {
+            Ok(_) => {}
+            Err(s) => {
+                // println!("{}", DisplayList::from(s));
+                // println!("{}", s.to_string());
+                println!("Report {:?}", s.with_source_code(format!("0o{:o}vv", b)));
+
+                // ErrReport::new(e);
+
+                return;
+            }
+        };
+        assert_eq!(format!("{:0>44o}", b), format!("{}", bv.to_oct()));
+    }
+
+    #[test]
+    fn test_to_hex() {
+        let mut bv = BitValue::new(128);
+        let a = i64::MAX.to_le_bytes();
+        let mut rb = [0u8; 128 / 8];
+        for (i, av) in a.iter().enumerate() {
+            rb[i] = *av;
+        }
+        rb[9] = 0xff;
+        let b = i128::from_le_bytes(rb);
+        println!("{:o}", b);
+        bv.parse_from(&format!("0x{:x}", b)).unwrap();
+        assert_eq!(format!("{:0>32x}", b), format!("{}", bv.to_hex()));
+    }
+
+    #[test]
+    fn test_to_dec() {
+        let mut bv = BitValue::new(128);
+        let a = i64::MAX.to_le_bytes();
+        let mut rb = [0u8; 128 / 8];
+        for (i, av) in a.iter().enumerate() {
+            rb[i] = *av;
+        }
+        rb[9] = 0xff;
+        let b = i128::from_le_bytes(rb);
+        println!("{}", b);
+        bv.parse_from(&format!("{}", b)).unwrap();
+        println!("{:0>40}", b);
+        println!("{}", bv.to_dec(true));
+        assert_eq!(format!("{:0>40}", b), format!("{}", bv.to_dec(true)));
+    }
+
+    #[test]
+    fn test_to_f64() {
+        let mut bv = BitValue::new(128);
+        let a = i64::MAX.to_le_bytes();
+        let mut rb = [0u8; 128 / 8];
+        for (i, av) in a.iter().enumerate() {
+            rb[i] = *av;
+        }
+        rb[9] = 0xff;
+        let b = i128::from_le_bytes(rb);
+        println!("{}", b);
+        bv.parse_from(&format!("{}", b)).unwrap();
+        println!("{:0>40}", b);
+        println!("{}", bv.to_f64(true));
+        let a = i128::MAX;
+        bv.set_size(BitValue::BITS).unwrap();
+        bv.parse_from(&format!("0x{:x}{:x}", a, a)).unwrap();
+        println!("{}", bv.to_f64(true));
+    }
+
+    #[test]
+    fn test_four_state() {
+        let mut bv = BitValue::new(4);
+        bv.parse_from("0b10xz").unwrap();
+        assert_eq!(bv.get_bit(0), Logic::Z);
+        assert_eq!(bv.get_bit(1), Logic::X);
+        assert_eq!(bv.get_bit(2), Logic::V0);
+        assert_eq!(bv.get_bit(3), Logic::V1);
+        assert_eq!(bv.to_bin(), "10xz");
+        assert_eq!(bv.to_hex(), "X");
+        assert_eq!(bv.to_dec(false), "X");
+        assert_eq!(bv.to_f64(false), 8.0);
+
+        let mut bv = BitValue::new(12);
+        bv.parse_from("0xz5x").unwrap();
+        assert_eq!(bv.to_hex(), "z5x");
+        assert_eq!(bv.to_bin(), "zzzz0101xxxx");
+        assert!(bv.has_unknown() && bv.has_high_z() && !bv.is_high_z());
+
+        bv.parse_from("z").unwrap();
+        assert!(bv.is_high_z());
+        assert_eq!(bv.to_dec(true), "z");
+        bv.parse_from("x").unwrap();
+        assert_eq!(bv.to_hex(), "xxx");
+        assert_eq!(bv.to_dec(true), "x");
+
+        bv.set_size(6).unwrap();
+        assert_eq!(bv.to_bin(), "xxxxxx");
+        bv.set_size(12).unwrap();
+        assert_eq!(bv.to_bin(), "000000xxxxxx");
+    }
+
+    #[test]
+    fn test_four_state_wire() {
+        let mut bv = BitValue::new(1);
+        bv.set_unknown();
+        assert_eq!(bv.to_bin(), "x");
+        bv.neg_bool();
+        assert_eq!(bv.to_bin(), "1");
+        bv.set_high_z();
+        assert!(bv.is_high_z());
+        bv.set_bool(false);
+        assert_eq!(bv.to_bin(), "0");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let mut bv = BitValue::new(4);
+        assert!(bv.parse_from("0b10201").is_err());
+        assert!(bv.parse_from("0x1f").is_err());
+        assert!(bv.parse_from("0x0f").is_ok());
+        assert!(bv.parse_from("0x").is_err());
+    }
+
+    #[test]
+    fn test_verilog_literal() {
+        let mut bv = BitValue::new(8);
+        bv.parse_from("8'hFF").unwrap();
+        assert_eq!(bv.to_hex(), "ff");
+        bv.parse_from("'d42").unwrap();
+        assert_eq!(bv.to_dec(false), "00000042");
+        bv.parse_from("-8'sd5").unwrap();
+        assert_eq!(bv.to_dec(true), "-00000005");
+        bv.parse_from(" 8 'b 1010_0101 ").unwrap();
+        assert_eq!(bv.to_bin(), "10100101");
+        bv.parse_from("8'o3_7_7").unwrap();
+        assert_eq!(bv.to_hex(), "ff");
+        bv.parse_from("0x1_f").unwrap();
+        assert_eq!(bv.to_hex(), "1f");
+        assert!(bv.parse_from("1_000").is_err());
+        bv.parse_from("2_55").unwrap();
+        assert_eq!(bv.to_hex(), "ff");
+
+        let mut bv = BitValue::new(16);
+        bv.parse_from("16'sb1111_0000").unwrap();
+        assert_eq!(bv.to_hex(), "00f0");
+        bv.parse_from("'1").unwrap();
+        assert_eq!(bv.to_hex(), "ffff");
+        bv.parse_from("'0").unwrap();
+        assert_eq!(bv.to_hex(), "0000");
+        bv.parse_from("'z").unwrap();
+        assert!(bv.is_high_z());
+        bv.parse_from("'hx").unwrap();
+        assert_eq!(bv.to_hex(), "xxxx");
+        bv.parse_from("'hz1").unwrap();
+        assert_eq!(bv.to_hex(), "zzz1");
+        bv.parse_from("'b1x").unwrap();
+        assert_eq!(bv.to_bin(), "000000000000001x");
+        bv.parse_from("16'dx").unwrap();
+        assert_eq!(bv.to_dec(false), "x");
+
+        let mut bv = BitValue::new(4);
+        bv.parse_from("4'bx").unwrap();
+        assert_eq!(bv.to_bin(), "xxxx");
+    }
+
+    #[test]
+    fn test_literal_errors() {
+        let span = |size: usize, s: &str| BitValue::new(size).parse_literal(s).err().unwrap().span;
+        assert_eq!(span(8, "16'hff"), (0, 2));
+        assert_eq!(span(8, "8'hfg"), (4, 1));
+        assert_eq!(span(8, "0'h0"), (0, 1));
+        assert_eq!(span(8, "8'"), (2, 0));
+        assert_eq!(span(8, "8'q1"), (2, 1));
+        assert_eq!(span(8, "8'h"), (3, 0));
+        assert_eq!(span(8, "8'h_1"), (3, 1));
+        assert_eq!(span(8, "8'h1ff"), (3, 1));
+        assert_eq!(span(8, "8'h1 2"), (5, 1));
+        assert_eq!(span(8, "8'1"), (2, 1));
+        assert_eq!(span(8, "0y1"), (1, 1));
+        assert_eq!(span(8, "  "), (0, 2));
+
+        let e = BitValue::new(8).parse_literal("16'hff").err().unwrap();
+        assert_eq!(e.label, "size 16 don't match wave width 8");
+    }
+
+    #[test]
+    fn test_wide() {
+        let hex: String = (0..2048 / 4).map(|i| ['f', '0', 'a', '5'][i % 4]).collect();
+        let mut bv = BitValue::new(2048);
+        bv.parse_from(&format!("0x{}", hex)).unwrap();
+        assert_eq!(bv.to_hex(), hex);
+        assert_eq!(bv.to_bin().len(), 2048);
+        assert!(bv.parse_from(&format!("0x1{}", hex)).is_err());
+
+        let dec = "340282366920938463463374607431768211456"; // 2^128
+        let mut bv = BitValue::new(129);
+        bv.parse_from(dec).unwrap();
+        assert_eq!(bv.to_dec(false), padded(&bv, dec));
+        assert_eq!(bv.to_hex(), format!("1{}", "0".repeat(32)));
+        assert!(BitValue::new(128).parse_from(dec).is_err());
+
+        bv.set_size(64).unwrap();
+        assert_eq!(bv.to_dec(false), "0".repeat(21));
+        assert!(matches!(bv.data, Words::Inline(0)));
+    }
+
+    #[test]
+    fn test_small_inline() {
+        let mut bv = BitValue::new(64);
+        bv.parse_from("0xffffffffffffffff").unwrap();
+        assert!(matches!(bv.data, Words::Inline(_)));
+        assert!(matches!(bv.unknown, Words::Inline(0)));
+        bv.set_unknown();
+        assert!(matches!(bv.unknown, Words::Inline(_)));
+    }
+
+    #[test]
+    fn test_old_layout() {
+        let old = "(bits_size:8,data:(255,0,0,0,0,0,0,0),neg:false,lsb:true)";
+        let bv: BitValue = ron::from_str(old).unwrap();
+        assert_eq!(bv.to_hex(), "ff");
+        assert!(!bv.has_unknown() && !bv.has_high_z());
+
+        let mut bv = BitValue::new(130);
+        bv.parse_from("0b1x").unwrap();
+        bv.set_bit(129, Logic::Z);
+        let s = ron::to_string(&bv).unwrap();
+        let nv: BitValue = ron::from_str(&s).unwrap();
+        assert_eq!(nv.to_bin(), bv.to_bin());
+    }
+
+    /// Decimal `s` zero padded like [`BitValue::to_dec`] of `bv`
+    fn padded(bv: &BitValue, s: &str) -> String {
+        let width = bv.dec_width();
+        match s.strip_prefix('-') {
+            Some(d) => format!("-{:0>width$}", d, width = width),
+            None => format!("{:0>width$}", s, width = width),
+        }
+    }
+
+    /// Small deterministic generator for test values
+    fn next(seed: &mut u128) -> u128 {
+        *seed = seed
+            .wrapping_mul(0x2360_ed05_1fc6_5da4_4385_df64_9fcc_f645)
+            .wrapping_add(0x5851_f42d_4c95_7f2d_1405_7b7e_f767_814f);
+        *seed ^ *seed >> 67
+    }
+
+    #[test]
+    fn test_native_conversion() {
+        let mut seed = 1u128;
+        for size in 1..=128usize {
+            let mask = u128::MAX >> (128 - size);
+            for _ in 0..64 {
+                let mut v = (next(&mut seed) >> (next(&mut seed) % 128)) & mask;
+                if v % 7 == 0 {
+                    v = mask;
+                }
+                let sv = ((v << (128 - size)) as i128) >> (128 - size);
+                let bv = BitValue::from_u128(size, v);
+                assert_eq!(bv.to_u128(), Some(v));
+                assert_eq!(bv.to_i128(), Some(sv));
+                assert_eq!(bv.to_f64(false), v as f64);
+                assert_eq!(bv.to_f64(true), sv as f64);
+                assert_eq!(BitValue::from_i128(size, sv), bv);
+                assert_eq!(bv.to_dec(false), padded(&bv, &v.to_string()));
+                assert_eq!(bv.to_dec(true), padded(&bv, &sv.to_string()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_wide_conversion() {
+        let mut seed = 7u128;
+        for size in [129usize, 200, 256, 1024, 2048] {
+            for _ in 0..64 {
+                let v = next(&mut seed);
+                let shift = (next(&mut seed) % (size as u128 - 127)) as usize;
+                let bv = &BitValue::from_u128(size, v) << shift;
+                assert_eq!(bv.to_f64(false), v as f64 * 2f64.powi(shift as i32));
+                let neg = -&bv;
+                if shift + 128 < size {
+                    assert_eq!(neg.to_f64(true), -(v as f64 * 2f64.powi(shift as i32)));
+                }
+                let fit = v == 0 || v.leading_zeros() as usize >= shift;
+                assert_eq!(
+                    bv.to_u128(),
+                    fit.then(|| v.checked_shl(shift as u32).unwrap_or(0))
+                );
+
+                let sv = v as i128;
+                let bv = BitValue::from_i128(size, sv);
+                assert_eq!(bv.to_i128(), Some(sv));
+                assert_eq!(bv.to_f64(true), sv as f64);
+                assert_eq!(bv.to_dec(true), padded(&bv, &sv.to_string()));
+            }
+        }
+        let mut max = BitValue::new(BitValue::BITS);
+        max.parse_from("-1").unwrap();
+        assert_eq!(max.to_f64(true), -1.0);
+        assert_eq!(max.to_f64(false), f64::INFINITY);
+        assert_eq!(max.to_i128(), Some(-1));
+        assert_eq!(max.to_u128(), None);
+
+        // 2^130 + 2^77 + 1: tie by 53 bit mantissa, lower bit must round up
+        let mut bv = BitValue::new(140);
+        bv.set_bit(130, Logic::V1);
+        bv.set_bit(77, Logic::V1);
+        assert_eq!(bv.to_f64(false), 2f64.powi(130));
+        bv.set_bit(0, Logic::V1);
+        assert_eq!(bv.to_f64(false), 2f64.powi(130) + 2f64.powi(78));
+    }
+
+    #[test]
+    fn test_unknown_conversion() {
+        let mut bv = BitValue::new(8);
+        bv.parse_from("0b1x01").unwrap();
+        assert_eq!(bv.to_u128(), None);
+        assert_eq!(bv.to_i128(), None);
+        assert_eq!(bv.to_f64(false), 9.0);
+    }
+
+    #[test]
+    fn test_from_f64() {
+        for v in [
+            0.0,
+            -0.0,
+            1.0,
+            -1.0,
+            2.5,
+            -2.5,
+            1e15,
+            -(2f64.powi(60)),
+            2f64.powi(100),
+        ] {
+            let bv = BitValue::from_f64(128, v).unwrap();
+            assert_eq!(bv.to_i128(), Some(v.trunc() as i128));
+        }
+        let bv = BitValue::from_f64(8, 300.0).unwrap();
+        assert_eq!(bv.to_dec(false), "00000044");
+        let bv = BitValue::from_f64(2048, 2f64.powi(1000)).unwrap();
+        assert_eq!(bv.to_f64(false), 2f64.powi(1000));
+        assert!(BitValue::from_f64(8, f64::NAN).is_none());
+    }
+}
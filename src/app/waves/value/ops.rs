@@ -0,0 +1,516 @@
+//! Verilog operators on [`BitValue`]. Result of binary operator has width of the widest operand,
+//! operands are zero extended and result wraps around on overflow.
+//! Arithmetic with any `x`/`z` bit gives all `x`, bitwise operators work per bit.
+
+use std::{
+    cmp::Ordering,
+    ops::{Add, BitAnd, BitOr, BitXor, Mul, Neg, Not, Shl, Shr, Sub},
+};
+
+use super::{words::Words, BitValue, Logic};
+
+impl BitValue {
+    pub fn from_u64(size: usize, v: u64) -> Self {
+        let mut r = BitValue::new(size);
+        r.data.set(0, v);
+        r.data.truncate(size);
+        r
+    }
+
+    /// Known value word, `x`/`z` bits and bits over size cleared
+    #[inline(always)]
+    pub(super) fn word(&self, i: usize) -> u64 {
+        self.data.get(i) & Words::mask(self.bits_size, i)
+    }
+
+    /// Mask of bits in `x` or `z` state
+    #[inline(always)]
+    fn unknown_word(&self, i: usize) -> u64 {
+        (self.unknown.get(i) | self.high_z.get(i)) & Words::mask(self.bits_size, i)
+    }
+
+    /// Value has only `0` and `1` bits
+    pub fn is_known(&self) -> bool {
+        !self.has_unknown() && !self.has_high_z()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.is_known() && (0..Words::count(self.bits_size)).all(|i| self.word(i) == 0)
+    }
+
+    /// Most significant bit
+    pub fn msb(&self) -> Logic {
+        match self.bits_size {
+            0 => Logic::V0,
+            n => self.get_bit(n - 1),
+        }
+    }
+
+    fn all_unknown(size: usize) -> Self {
+        let mut r = BitValue::new(size);
+        r.set_unknown();
+        r
+    }
+
+    /// Build value of `size` from (value, `x` mask) words
+    fn from_words<F: FnMut(usize) -> (u64, u64)>(size: usize, mut f: F) -> Self {
+        let mut r = BitValue::new(size);
+        for i in 0..Words::count(size) {
+            let (v, x) = f(i);
+            let mask = Words::mask(size, i);
+            r.data.set(i, v & !x & mask);
+            r.unknown.set(i, x & mask);
+        }
+        r
+    }
+
+    /// Apply `f` to every bit plane, `x` and `z` states are kept
+    fn map_planes<F: Fn(&Words, usize) -> u64>(&self, size: usize, f: F) -> Self {
+        let mut r = BitValue::new(size);
+        r.lsb = self.lsb;
+        for (src, dst) in [
+            (&self.data, &mut r.data),
+            (&self.unknown, &mut r.unknown),
+            (&self.high_z, &mut r.high_z),
+        ] {
+            for i in 0..Words::count(size) {
+                dst.set(i, f(src, i) & Words::mask(size, i));
+            }
+        }
+        r
+    }
+
+    /// Zero extend or truncate to `size`
+    pub fn zero_extend(&self, size: usize) -> Self {
+        let bits = self.bits_size;
+        self.map_planes(size, |p, i| p.get(i) & Words::mask(bits, i))
+    }
+
+    /// Extend to `size` by copies of most significant bit, truncate if `size` is smaller
+    pub fn sign_extend(&self, size: usize) -> Self {
+        let mut r = self.zero_extend(size);
+        if size <= self.bits_size {
+            return r;
+        }
+        let plane = match self.msb() {
+            Logic::V0 => return r,
+            Logic::V1 => &mut r.data,
+            Logic::X => &mut r.unknown,
+            Logic::Z => &mut r.high_z,
+        };
+        for i in self.bits_size / Words::BITS..Words::count(size) {
+            let ext = Words::mask(size, i) & !Words::mask(self.bits_size, i);
+            plane.update(i, |w| w | ext);
+        }
+        r
+    }
+
+    /// Part select `[msb:lsb]`. Bits out of value are `x`, as in verilog
+    pub fn slice(&self, msb: usize, lsb: usize) -> Self {
+        assert!(msb >= lsb, "Slice [{msb}:{lsb}] must have msb >= lsb");
+        let size = msb - lsb + 1;
+        let mut r = self
+            .zero_extend(self.bits_size)
+            .map_planes(size, |p, i| shifted_right(p, lsb, i));
+        for b in self.bits_size.saturating_sub(lsb)..size {
+            r.set_bit(b, Logic::X);
+        }
+        r
+    }
+
+    /// Write `v` into part `[lsb + size - 1:lsb]`, bits out of value dropped
+    pub fn set_slice(&mut self, lsb: usize, v: &BitValue) {
+        for b in 0..v.bits_size.min(self.bits_size.saturating_sub(lsb)) {
+            self.set_bit(lsb + b, v.get_bit(b));
+        }
+    }
+
+    /// Arithmetic shift right, fill by most significant bit
+    pub fn ashr(&self, n: usize) -> Self {
+        let n = n.min(self.bits_size);
+        (&self.sign_extend(self.bits_size + n) >> n).zero_extend(self.bits_size)
+    }
+
+    /// Compare as two's complement numbers. `None` if any `x`/`z` bit
+    pub fn cmp_signed(&self, other: &Self) -> Option<Ordering> {
+        if !self.is_known() || !other.is_known() {
+            return (self == other).then_some(Ordering::Equal);
+        }
+        let size = self.bits_size.max(other.bits_size);
+        let (a, b) = (self.sign_extend(size), other.sign_extend(size));
+        match (a.msb(), b.msb()) {
+            (Logic::V1, Logic::V0) => Some(Ordering::Less),
+            (Logic::V0, Logic::V1) => Some(Ordering::Greater),
+            _ => a.partial_cmp(&b),
+        }
+    }
+
+    fn add_words(a: &Self, b: &Self, sub: bool) -> Self {
+        let size = a.bits_size.max(b.bits_size);
+        if !a.is_known() || !b.is_known() {
+            return Self::all_unknown(size);
+        }
+        let mut carry = sub as u64;
+        Self::from_words(size, |i| {
+            let bw = if sub { !b.word(i) } else { b.word(i) };
+            let (s, c1) = a.word(i).overflowing_add(bw);
+            let (s, c2) = s.overflowing_add(carry);
+            carry = (c1 || c2) as u64;
+            (s, 0)
+        })
+    }
+
+    fn mul_words(a: &Self, b: &Self) -> Self {
+        let size = a.bits_size.max(b.bits_size);
+        if !a.is_known() || !b.is_known() {
+            return Self::all_unknown(size);
+        }
+        let n = Words::count(size);
+        let mut acc = vec![0u64; n];
+        for i in 0..n {
+            let ai = a.word(i) as u128;
+            if ai == 0 {
+                continue;
+            }
+            let mut carry = 0u128;
+            for j in 0..n - i {
+                let cur = acc[i + j] as u128 + ai * b.word(j) as u128 + carry;
+                acc[i + j] = cur as u64;
+                carry = cur >> 64;
+            }
+        }
+        Self::from_words(size, |i| (acc[i], 0))
+    }
+
+    /// Per bit operation. `f` get (value, `x` mask) words of both operands
+    fn bitwise<F: Fn((u64, u64), (u64, u64)) -> (u64, u64)>(a: &Self, b: &Self, f: F) -> Self {
+        let size = a.bits_size.max(b.bits_size);
+        Self::from_words(size, |i| {
+            f(
+                (a.word(i), a.unknown_word(i)),
+                (b.word(i), b.unknown_word(i)),
+            )
+        })
+    }
+}
+
+fn shifted_left(w: &Words, n: usize, i: usize) -> u64 {
+    let (q, r) = (n / Words::BITS, n % Words::BITS);
+    if i < q {
+        return 0;
+    }
+    let lo = if r == 0 || i == q {
+        0
+    } else {
+        w.get(i - q - 1) >> (Words::BITS - r)
+    };
+    (w.get(i - q) << r) | lo
+}
+
+fn shifted_right(w: &Words, n: usize, i: usize) -> u64 {
+    let (q, r) = (n / Words::BITS, n % Words::BITS);
+    let hi = if r == 0 {
+        0
+    } else {
+        w.get(i + q + 1) << (Words::BITS - r)
+    };
+    (w.get(i + q) >> r) | hi
+}
+
+fn and((av, ax): (u64, u64), (bv, bx): (u64, u64)) -> (u64, u64) {
+    let zero = (!av & !ax) | (!bv & !bx);
+    let one = av & !ax & bv & !bx;
+    (one, !(zero | one))
+}
+
+fn or((av, ax): (u64, u64), (bv, bx): (u64, u64)) -> (u64, u64) {
+    let one = (av & !ax) | (bv & !bx);
+    let zero = !av & !ax & !bv & !bx;
+    (one, !(zero | one))
+}
+
+fn xor((av, ax): (u64, u64), (bv, bx): (u64, u64)) -> (u64, u64) {
+    (av ^ bv, ax | bx)
+}
+
+macro_rules! binary_op {
+    ($tr:ident, $method:ident, |$a:ident, $b:ident| $body:expr) => {
+        impl $tr<&BitValue> for &BitValue {
+            type Output = BitValue;
+
+            fn $method(self, rhs: &BitValue) -> BitValue {
+                let ($a, $b) = (self, rhs);
+                $body
+            }
+        }
+
+        impl $tr for BitValue {
+            type Output = BitValue;
+
+            fn $method(self, rhs: BitValue) -> BitValue {
+                (&self).$method(&rhs)
+            }
+        }
+    };
+}
+
+binary_op!(Add, add, |a, b| BitValue::add_words(a, b, false));
+binary_op!(Sub, sub, |a, b| BitValue::add_words(a, b, true));
+binary_op!(Mul, mul, |a, b| BitValue::mul_words(a, b));
+binary_op!(BitAnd, bitand, |a, b| BitValue::bitwise(a, b, and));
+binary_op!(BitOr, bitor, |a, b| BitValue::bitwise(a, b, or));
+binary_op!(BitXor, bitxor, |a, b| BitValue::bitwise(a, b, xor));
+
+impl Not for &BitValue {
+    type Output = BitValue;
+
+    fn not(self) -> BitValue {
+        BitValue::from_words(self.bits_size, |i| (!self.word(i), self.unknown_word(i)))
+    }
+}
+
+impl Not for BitValue {
+    type Output = BitValue;
+
+    fn not(self) -> BitValue {
+        !&self
+    }
+}
+
+/// Two's complement negation
+impl Neg for &BitValue {
+    type Output = BitValue;
+
+    fn neg(self) -> BitValue {
+        &BitValue::new(self.bits_size) - self
+    }
+}
+
+impl Neg for BitValue {
+    type Output = BitValue;
+
+    fn neg(self) -> BitValue {
+        -&self
+    }
+}
+
+/// Logical shift, width is kept
+impl Shl<usize> for &BitValue {
+    type Output = BitValue;
+
+    fn shl(self, n: usize) -> BitValue {
+        let v = self.zero_extend(self.bits_size);
+        v.map_planes(self.bits_size, |p, i| shifted_left(p, n, i))
+    }
+}
+
+impl Shl<usize> for BitValue {
+    type Output = BitValue;
+
+    fn shl(self, n: usize) -> BitValue {
+        &self << n
+    }
+}
+
+/// Logical shift, width is kept
+impl Shr<usize> for &BitValue {
+    type Output = BitValue;
+
+    fn shr(self, n: usize) -> BitValue {
+        let v = self.zero_extend(self.bits_size);
+        v.map_planes(self.bits_size, |p, i| shifted_right(p, n, i))
+    }
+}
+
+impl Shr<usize> for BitValue {
+    type Output = BitValue;
+
+    fn shr(self, n: usize) -> BitValue {
+        &self >> n
+    }
+}
+
+/// Case equality (`===`) of zero extended values
+impl PartialEq for BitValue {
+    fn eq(&self, other: &Self) -> bool {
+        let size = self.bits_size.max(other.bits_size);
+        (0..Words::count(size)).all(|i| {
+            self.word(i) == other.word(i)
+                && self.unknown.get(i) & Words::mask(self.bits_size, i)
+                    == other.unknown.get(i) & Words::mask(other.bits_size, i)
+                && self.high_z.get(i) & Words::mask(self.bits_size, i)
+                    == other.high_z.get(i) & Words::mask(other.bits_size, i)
+        })
+    }
+}
+
+impl Eq for BitValue {}
+
+/// Unsigned compare. `None` if any `x`/`z` bit and values are not the same
+impl PartialOrd for BitValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if !self.is_known() || !other.is_known() {
+            return (self == other).then_some(Ordering::Equal);
+        }
+        let size = self.bits_size.max(other.bits_size);
+        (0..Words::count(size))
+            .rev()
+            .map(|i| self.word(i).cmp(&other.word(i)))
+            .find(|o| o.is_ne())
+            .or(Some(Ordering::Equal))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cmp::Ordering;
+
+    use super::{BitValue, Logic};
+
+    fn bv(size: usize, s: &str) -> BitValue {
+        let mut v = BitValue::new(size);
+        v.parse_from(s).unwrap();
+        v
+    }
+
+    fn from_u128(size: usize, v: u128) -> BitValue {
+        bv(size, &format!("0x{:x}", v & (u128::MAX >> (128 - size))))
+    }
+
+    #[test]
+    fn test_arith_wrap() {
+        let samples = [0u8, 1, 2, 3, 7, 100, 127, 128, 200, 254, 255];
+        for a in samples {
+            for b in samples {
+                let (va, vb) = (
+                    BitValue::from_u64(8, a as u64),
+                    BitValue::from_u64(8, b as u64),
+                );
+                assert_eq!(&va + &vb, BitValue::from_u64(8, a.wrapping_add(b) as u64));
+                assert_eq!(&va - &vb, BitValue::from_u64(8, a.wrapping_sub(b) as u64));
+                assert_eq!(&va * &vb, BitValue::from_u64(8, a.wrapping_mul(b) as u64));
+                assert_eq!(&va & &vb, BitValue::from_u64(8, (a & b) as u64));
+                assert_eq!(&va | &vb, BitValue::from_u64(8, (a | b) as u64));
+                assert_eq!(&va ^ &vb, BitValue::from_u64(8, (a ^ b) as u64));
+                assert_eq!(va.partial_cmp(&vb), Some(a.cmp(&b)));
+                assert_eq!(va.cmp_signed(&vb), Some((a as i8).cmp(&(b as i8))));
+            }
+            let va = BitValue::from_u64(8, a as u64);
+            assert_eq!(!&va, BitValue::from_u64(8, !a as u64));
+            assert_eq!(-&va, BitValue::from_u64(8, a.wrapping_neg() as u64));
+            for n in 0..9 {
+                assert_eq!(
+                    &va << n,
+                    BitValue::from_u64(8, a.checked_shl(n as u32).unwrap_or(0) as u64)
+                );
+                assert_eq!(
+                    &va >> n,
+                    BitValue::from_u64(8, a.checked_shr(n as u32).unwrap_or(0) as u64)
+                );
+                assert_eq!(
+                    va.ashr(n),
+                    BitValue::from_u64(
+                        8,
+                        (a as i8).checked_shr(n as u32).unwrap_or((a as i8) >> 7) as u8 as u64
+                    )
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_multi_word() {
+        let samples = [
+            0u128,
+            1,
+            u64::MAX as u128,
+            u64::MAX as u128 + 1,
+            0x1234_5678_9abc_def0_0fed_cba9_8765_4321,
+            u128::MAX,
+            u128::MAX >> 1,
+        ];
+        for a in samples {
+            for b in samples {
+                let (va, vb) = (from_u128(128, a), from_u128(128, b));
+                assert_eq!(&va + &vb, from_u128(128, a.wrapping_add(b)));
+                assert_eq!(&va - &vb, from_u128(128, a.wrapping_sub(b)));
+                assert_eq!(&va * &vb, from_u128(128, a.wrapping_mul(b)));
+                assert_eq!(va.partial_cmp(&vb), Some(a.cmp(&b)));
+                assert_eq!(va.cmp_signed(&vb), Some((a as i128).cmp(&(b as i128))));
+            }
+            let va = from_u128(128, a);
+            for n in [0, 1, 63, 64, 65, 100, 127, 128] {
+                assert_eq!(
+                    &va << n,
+                    from_u128(128, a.checked_shl(n as u32).unwrap_or(0))
+                );
+                assert_eq!(
+                    &va >> n,
+                    from_u128(128, a.checked_shr(n as u32).unwrap_or(0))
+                );
+            }
+            let va = from_u128(100, a);
+            assert_eq!(&va + &from_u128(100, 1), from_u128(100, a.wrapping_add(1)));
+        }
+    }
+
+    #[test]
+    fn test_width() {
+        let a = BitValue::from_u64(4, 0xf);
+        let b = BitValue::from_u64(8, 0x01);
+        assert_eq!(&a + &b, BitValue::from_u64(8, 0x10));
+        assert_eq!(a.sign_extend(8), BitValue::from_u64(8, 0xff));
+        assert_eq!(a.zero_extend(8), BitValue::from_u64(8, 0x0f));
+        assert_eq!(
+            BitValue::from_u64(8, 0xa5).slice(7, 4),
+            BitValue::from_u64(4, 0xa)
+        );
+        assert_eq!(
+            BitValue::from_u64(8, 0xa5).slice(3, 0),
+            BitValue::from_u64(4, 0x5)
+        );
+        assert_eq!(bv(8, "0xa5").slice(9, 6).to_bin(), "xx10");
+        let wide = from_u128(128, 0xdead_beef << 60);
+        assert_eq!(wide.slice(91, 60), BitValue::from_u64(32, 0xdead_beef));
+        let mut v = bv(8, "0xa5");
+        v.set_slice(6, &bv(4, "4'bx011"));
+        assert_eq!(v.to_bin(), "11100101");
+        v.set_slice(0, &bv(2, "2'bz0"));
+        assert_eq!(v.to_bin(), "111001z0");
+        assert_eq!(
+            BitValue::from_u64(8, 0x80).sign_extend(130).to_hex().len(),
+            33
+        );
+        assert_eq!(BitValue::from_u64(4, 0x1), BitValue::from_u64(16, 0x1));
+    }
+
+    #[test]
+    fn test_four_state_ops() {
+        let a = bv(4, "0b01xz");
+        let zero = BitValue::from_u64(4, 0);
+        let ones = BitValue::from_u64(4, 0xf);
+        assert_eq!((&a & &zero).to_bin(), "0000");
+        assert_eq!((&a & &ones).to_bin(), "01xx");
+        assert_eq!((&a | &ones).to_bin(), "1111");
+        assert_eq!((&a | &zero).to_bin(), "01xx");
+        assert_eq!((&a ^ &zero).to_bin(), "01xx");
+        assert_eq!((!&a).to_bin(), "10xx");
+        assert_eq!((&a + &zero).to_bin(), "xxxx");
+        assert_eq!((&a << 1).to_bin(), "1xz0");
+        assert_eq!(a.sign_extend(6).to_bin(), "0001xz");
+        assert_eq!(bv(2, "0bz1").sign_extend(4).to_bin(), "zzz1");
+        assert_eq!(a.partial_cmp(&ones), None);
+        assert_eq!(a.partial_cmp(&a.clone()), Some(Ordering::Equal));
+        assert_eq!(a.msb(), Logic::V0);
+    }
+
+    #[test]
+    fn test_negative_literal() {
+        let a = bv(8, "-5");
+        assert_eq!(a, BitValue::from_u64(8, 0xfb));
+        assert_eq!(a.to_dec(true), "-00000005");
+        assert_eq!(a.to_dec(false), "00000251");
+        assert_eq!(bv(8, "-128").to_dec(true), "-00000128");
+        assert_eq!((&a + &BitValue::from_u64(8, 5)).to_dec(true), "00000000");
+    }
+}
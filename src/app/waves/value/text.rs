@@ -0,0 +1,210 @@
+//! Packed ASCII interpretation of [`BitValue`], like verilog string in `reg [8*n-1:0]`:
+//! first character in the most significant byte, last one in the lowest byte.
+
+use miette::{ErrReport, LabeledSpan, MietteDiagnostic};
+
+use super::{BitValue, Logic};
+
+impl BitValue {
+    /// Bytes from most significant one, top byte may be partial. `None` for byte with `x`/`z` bit
+    fn bytes(&self) -> Vec<Option<u8>> {
+        let count = (self.bits_size + 7) / 8;
+        (0..count)
+            .rev()
+            .map(|i| {
+                let mut byte = 0u8;
+                for b in i * 8..(i * 8 + 8).min(self.bits_size) {
+                    match self.get_bit(b) {
+                        Logic::V0 => {}
+                        Logic::V1 => byte |= 1 << (b - i * 8),
+                        Logic::X | Logic::Z => return None,
+                    }
+                }
+                Some(byte)
+            })
+            .collect()
+    }
+
+    /// Characters like verilog `%s`: leading zero bytes skipped.
+    /// Not printable byte shown as `.`, byte with `x`/`z` bits as `?`
+    pub fn to_ascii(&self) -> String {
+        self.bytes()
+            .into_iter()
+            .skip_while(|b| *b == Some(0))
+            .map(|b| match b {
+                Some(c) if c.is_ascii_graphic() || c == b' ' => c as char,
+                Some(_) => '.',
+                None => '?',
+            })
+            .collect()
+    }
+
+    /// Pack bytes MSB first, bytes over `size` dropped
+    pub fn from_bytes(size: usize, bytes: &[u8]) -> Self {
+        let mut value = BitValue::new(size);
+        for (n, byte) in bytes.iter().rev().enumerate() {
+            for b in (0..8).take_while(|b| n * 8 + b < size) {
+                if byte >> b & 0b1 != 0 {
+                    value.set_bit(n * 8 + b, Logic::V1);
+                }
+            }
+        }
+        value
+    }
+
+    /// Text for editor. Printable string as is, otherwise quoted with escapes
+    pub fn to_ascii_literal(&self) -> String {
+        let bytes: Vec<_> = self
+            .bytes()
+            .into_iter()
+            .skip_while(|b| *b == Some(0))
+            .collect();
+        let plain = bytes
+            .iter()
+            .all(|b| matches!(b, Some(c) if c.is_ascii_graphic() || *c == b' '))
+            && !matches!(bytes.first(), Some(Some(b'"')));
+        if plain {
+            return self.to_ascii();
+        }
+        let mut s = String::from("\"");
+        for b in bytes {
+            match b {
+                Some(b'"') => s += "\\\"",
+                Some(b'\\') => s += "\\\\",
+                Some(b'\n') => s += "\\n",
+                Some(b'\t') => s += "\\t",
+                Some(c) if c.is_ascii_graphic() || c == b' ' => s.push(c as char),
+                Some(c) => s += &format!("\\x{:02x}", c),
+                None => s.push('?'),
+            }
+        }
+        s + "\""
+    }
+
+    /// Pack string MSB first, upper bytes are zero. String in `"` may have escapes:
+    /// `\n`, `\t`, `\\`, `\"` and `\xHH`
+    pub fn parse_ascii(&mut self, s: &str) -> Result<(), ErrReport> {
+        match self.ascii_bytes(s) {
+            Ok(value) => {
+                self.assign(value);
+                Ok(())
+            }
+            Err((span, label)) => Err(ErrReport::new(
+                MietteDiagnostic::new("Error by parsing string")
+                    .with_code(s)
+                    .with_severity(miette::Severity::Error)
+                    .with_help(
+                        "String must be ASCII and fit in register, 8 bits for each character. \
+                        Quoted string may have escapes \\n, \\t, \\\\, \\\" and \\xHH",
+                    )
+                    .with_label(LabeledSpan::at(span, label)),
+            )),
+        }
+    }
+
+    /// Error is byte span in source and label
+    fn ascii_bytes(&self, s: &str) -> Result<BitValue, ((usize, usize), &'static str)> {
+        let mut bytes: Vec<(usize, usize, u8)> = Vec::with_capacity(s.len());
+        if let Some(inner) = s.strip_prefix('"') {
+            let Some(inner) = inner.strip_suffix('"').filter(|_| s.len() > 1) else {
+                return Err(((s.len(), 0), "expected closing \""));
+            };
+            let mut chars = inner.char_indices().map(|(i, c)| (i + 1, c)).peekable();
+            while let Some((at, c)) = chars.next() {
+                if c != '\\' {
+                    if !c.is_ascii() {
+                        return Err(((at, c.len_utf8()), "only ASCII characters allowed"));
+                    }
+                    bytes.push((at, 1, c as u8));
+                    continue;
+                }
+                let byte = match chars.next() {
+                    Some((_, 'n')) => b'\n',
+                    Some((_, 't')) => b'\t',
+                    Some((_, '\\')) => b'\\',
+                    Some((_, '"')) => b'"',
+                    Some((_, 'x')) => {
+                        let mut v = 0u32;
+                        for _ in 0..2 {
+                            match chars.peek().and_then(|(_, c)| c.to_digit(16)) {
+                                Some(d) => v = v * 16 + d,
+                                None => return Err(((at, 2), "expected two hex digits")),
+                            }
+                            chars.next();
+                        }
+                        v as u8
+                    }
+                    Some((i, c)) => return Err(((at, i + c.len_utf8() - at), "unknown escape")),
+                    None => return Err(((at, 1), "unfinished escape")),
+                };
+                let len = chars.peek().map_or(s.len() - 1, |(i, _)| *i) - at;
+                bytes.push((at, len, byte));
+            }
+        } else {
+            for (at, c) in s.char_indices() {
+                if !c.is_ascii() {
+                    return Err(((at, c.len_utf8()), "only ASCII characters allowed"));
+                }
+                bytes.push((at, 1, c as u8));
+            }
+        }
+
+        let mut value = BitValue::new(self.bits_size);
+        for (n, (at, len, byte)) in bytes.into_iter().rev().enumerate() {
+            for b in 0..8 {
+                if byte >> b & 0b1 == 0 {
+                    continue;
+                }
+                if n * 8 + b >= self.bits_size {
+                    return Err(((at, len), "string is too long for register"));
+                }
+                value.set_bit(n * 8 + b, Logic::V1);
+            }
+        }
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::BitValue;
+
+    #[test]
+    fn test_ascii() {
+        let mut bv = BitValue::new(40);
+        bv.parse_ascii("HELLO").unwrap();
+        assert_eq!(bv.to_hex(), "48454c4c4f");
+        assert_eq!(bv.to_ascii(), "HELLO");
+        bv.parse_ascii("Hi").unwrap();
+        assert_eq!(bv.to_hex(), "0000004869");
+        assert_eq!(bv.to_ascii(), "Hi");
+        assert_eq!(bv.to_ascii_literal(), "Hi");
+
+        bv.parse_ascii("\"a\\n\\x01\\\"\"").unwrap();
+        assert_eq!(bv.to_hex(), "00610a0122");
+        assert_eq!(bv.to_ascii(), "a..\"");
+        assert_eq!(bv.to_ascii_literal(), "\"a\\n\\x01\\\"\"");
+
+        bv.set_bit(0, super::Logic::X);
+        assert_eq!(bv.to_ascii(), "a..?");
+
+        // Partial top byte holds only small values
+        let mut bv = BitValue::new(12);
+        bv.parse_ascii("\"\\x0fA\"").unwrap();
+        assert_eq!(bv.to_hex(), "f41");
+        assert!(bv.parse_ascii("AB").is_err());
+        assert_eq!(BitValue::from_bytes(12, b"AB").to_hex(), "142");
+    }
+
+    #[test]
+    fn test_ascii_errors() {
+        let bv = BitValue::new(16);
+        let span = |s: &str| bv.ascii_bytes(s).err().unwrap().0;
+        assert_eq!(span("ABC"), (0, 1));
+        assert_eq!(span("Aé"), (1, 2));
+        assert_eq!(span("\"A"), (2, 0));
+        assert_eq!(span("\"\\q\""), (1, 2));
+        assert_eq!(span("\"\\x1\""), (1, 2));
+        assert_eq!(span("\"\\x41\\x42\\x43\""), (1, 4));
+    }
+}
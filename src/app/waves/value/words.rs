@@ -0,0 +1,194 @@
+use std::fmt;
+
+use serde::{
+    de::{SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+/// Little endian 64 bit words of arbitrary length. Missing words read as zero.
+/// Value that fit in one word is kept without heap allocation.
+#[derive(Clone, Debug)]
+pub(super) enum Words {
+    Inline(u64),
+    Heap(Vec<u64>),
+}
+
+impl Default for Words {
+    fn default() -> Self {
+        Words::Inline(0)
+    }
+}
+
+impl Words {
+    pub const BITS: usize = u64::BITS as usize;
+
+    /// Words needed for `bits` bits
+    pub fn count(bits: usize) -> usize {
+        (bits + Self::BITS - 1) / Self::BITS
+    }
+
+    /// Mask of used bits in word `i` for value of `bits` size
+    pub fn mask(bits: usize, i: usize) -> u64 {
+        let full = bits / Self::BITS;
+        if i < full {
+            !0u64
+        } else if i == full && bits % Self::BITS != 0 {
+            !(!0u64 << (bits % Self::BITS))
+        } else {
+            0
+        }
+    }
+
+    #[inline(always)]
+    pub fn get(&self, i: usize) -> u64 {
+        match self {
+            Words::Inline(v) => {
+                if i == 0 {
+                    *v
+                } else {
+                    0
+                }
+            }
+            Words::Heap(h) => h.get(i).copied().unwrap_or(0),
+        }
+    }
+
+    pub fn set(&mut self, i: usize, w: u64) {
+        match self {
+            Words::Inline(v) if i == 0 => *v = w,
+            Words::Inline(_) if w == 0 => {}
+            Words::Inline(v) => {
+                let mut h = vec![0u64; i + 1];
+                h[0] = *v;
+                h[i] = w;
+                *self = Words::Heap(h);
+            }
+            Words::Heap(h) => {
+                if i >= h.len() {
+                    if w == 0 {
+                        return;
+                    }
+                    h.resize(i + 1, 0);
+                }
+                h[i] = w;
+            }
+        }
+    }
+
+    #[inline(always)]
+    pub fn update<F: FnOnce(u64) -> u64>(&mut self, i: usize, f: F) {
+        let w = f(self.get(i));
+        self.set(i, w);
+    }
+
+    pub fn get_ref(&self, i: usize) -> &u64 {
+        const ZERO: &u64 = &0;
+        match self {
+            Words::Inline(v) if i == 0 => v,
+            Words::Inline(_) => ZERO,
+            Words::Heap(h) => h.get(i).unwrap_or(ZERO),
+        }
+    }
+
+    pub fn get_mut(&mut self, i: usize) -> &mut u64 {
+        if let Words::Inline(v) = *self {
+            if i == 0 {
+                let Words::Inline(v) = self else {
+                    unreachable!()
+                };
+                return v;
+            }
+            *self = Words::Heap(vec![v]);
+        }
+        let Words::Heap(h) = self else { unreachable!() };
+        if i >= h.len() {
+            h.resize(i + 1, 0);
+        }
+        &mut h[i]
+    }
+
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Words::Inline(v) => *v == 0,
+            Words::Heap(h) => h.iter().all(|v| *v == 0),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Words::Inline(0);
+    }
+
+    /// Clear bits from `bits` and drop unused words
+    pub fn truncate(&mut self, bits: usize) {
+        let full = bits / Self::BITS;
+        if let Words::Heap(h) = self {
+            h.truncate(Self::count(bits));
+        }
+        if bits % Self::BITS != 0 {
+            self.update(full, |w| w & Self::mask(bits, full));
+        } else if bits == 0 {
+            self.clear();
+        }
+        self.shrink();
+    }
+
+    fn shrink(&mut self) {
+        if let Words::Heap(h) = self {
+            while h.len() > 1 && h.last() == Some(&0) {
+                h.pop();
+            }
+            if h.len() <= 1 {
+                *self = Words::Inline(h.first().copied().unwrap_or(0));
+            }
+        }
+    }
+}
+
+impl PartialEq for Words {
+    fn eq(&self, other: &Self) -> bool {
+        let len = match (self, other) {
+            (Words::Heap(a), Words::Heap(b)) => a.len().max(b.len()),
+            (Words::Heap(a), _) | (_, Words::Heap(a)) => a.len(),
+            _ => 1,
+        };
+        (0..len).all(|i| self.get(i) == other.get(i))
+    }
+}
+
+impl Eq for Words {}
+
+impl Serialize for Words {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Words::Inline(v) => serializer.collect_seq([v]),
+            Words::Heap(h) => serializer.collect_seq(h),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Words {
+    /// Also accept fixed size array of old project files
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct WordsVisitor;
+
+        impl<'de> Visitor<'de> for WordsVisitor {
+            type Value = Words;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "sequence of u64 words")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Words, A::Error> {
+                let mut h = Vec::with_capacity(seq.size_hint().unwrap_or(1));
+                while let Some(w) = seq.next_element()? {
+                    h.push(w);
+                }
+                let mut words = Words::Heap(h);
+                words.shrink();
+                Ok(words)
+            }
+        }
+
+        deserializer.deserialize_any(WordsVisitor)
+    }
+}
@@ -2,11 +2,25 @@ use std::fmt::Display;
 
 use serde::{Deserialize, Serialize};
 
+use super::WaveId;
+
 #[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub struct Clock {
     pub period: usize,
     pub duty: usize,
     pub phase: usize,
+    /// Cycles held low before first period
+    #[serde(default)]
+    pub start: usize,
+    /// Cycle from which clock is held low
+    #[serde(default)]
+    pub stop: Option<usize>,
+    /// Clock enable, clock is low in cycles where bit 0 of wave is not `1`
+    #[serde(default)]
+    pub gate: Option<WaveId>,
+    /// Exported as complementary pair `_p` and `_n`
+    #[serde(default)]
+    pub differential: bool,
 }
 
 impl Clock {
@@ -15,7 +29,42 @@ impl Clock {
             period: 2,
             duty: 1,
             phase: 0,
+            start: 0,
+            stop: None,
+            gate: None,
+            differential: false,
+        }
+    }
+
+    /// Level of clock in `cycle` without gate
+    pub fn level(&self, cycle: usize) -> bool {
+        let stopped = self.stop.map_or(false, |s| cycle >= s);
+        if cycle < self.start || stopped {
+            return false;
+        }
+        (cycle - self.start + self.phase) % self.period < self.duty
+    }
+
+    /// Verilog expression of level in cycle counted by variable `cycle`,
+    /// `gate` is expression of enable bit
+    pub fn export_level(&self, cycle: &str, gate: Option<&str>) -> String {
+        let mut terms = Vec::new();
+        let mut position = cycle.to_string();
+        if self.start > 0 {
+            terms.push(format!("{} >= {}", cycle, self.start));
+            position = format!("{} - {}", cycle, self.start);
+        }
+        if let Some(stop) = self.stop {
+            terms.push(format!("{} < {}", cycle, stop));
+        }
+        if self.phase > 0 {
+            position = format!("{} + {}", position, self.phase);
+        }
+        terms.push(format!("({}) % {} < {}", position, self.period, self.duty));
+        if let Some(g) = gate {
+            terms.push(format!("{} === 1'b1", g));
         }
+        terms.join(" && ")
     }
 }
 
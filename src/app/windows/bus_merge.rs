@@ -0,0 +1,89 @@
+use crate::{
+    app::waves::{BitValue, Wave, WaveId},
+    hseparator,
+};
+
+use super::WindowResult;
+
+struct MergePart {
+    id: WaveId,
+    name: String,
+    size: usize,
+    merge: bool,
+}
+
+/// Concatenation of chosen waves into one register, first wave is most significant part
+pub struct BusMerge {
+    pub name: String,
+    /// Chosen waves become linked slices of new register
+    pub linked: bool,
+    parts: Vec<MergePart>,
+}
+
+impl BusMerge {
+    /// Waves with selected cycles and wave `from` are chosen at start
+    pub fn new(waves: &[Wave], from: usize) -> Self {
+        let parts = waves
+            .iter()
+            .enumerate()
+            .filter(|(_, w)| w.editable())
+            .map(|(i, w)| MergePart {
+                id: w.id(),
+                name: w.name(),
+                size: w.reg_size(),
+                merge: i == from || w.has_selection(),
+            })
+            .collect();
+        Self {
+            name: format!("{}_bus", waves[from].name()),
+            linked: true,
+            parts,
+        }
+    }
+
+    /// Ids of chosen waves in project order
+    pub fn chosen(&self) -> Vec<WaveId> {
+        self.parts
+            .iter()
+            .filter(|p| p.merge)
+            .map(|p| p.id)
+            .collect()
+    }
+
+    pub fn display(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) -> WindowResult {
+        let mut state = WindowResult::Open;
+        let mut open = true;
+        egui::Window::new("Merge into bus")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Bus name");
+                    ui.text_edit_singleline(&mut self.name);
+                });
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        for p in &mut self.parts {
+                            ui.checkbox(&mut p.merge, format!("{} [{}]", p.name, p.size));
+                        }
+                    });
+                let size: usize = self.parts.iter().filter(|p| p.merge).map(|p| p.size).sum();
+                ui.label(format!("Bus size: {}", size));
+                ui.checkbox(&mut self.linked, "Linked: edits go to bus and back");
+                hseparator!(ui);
+                let valid = (1..=BitValue::BITS).contains(&size);
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(valid, egui::Button::new("Save")).clicked() {
+                        state = WindowResult::Save;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        state = WindowResult::Cancel;
+                    }
+                });
+            });
+        if !open {
+            state = WindowResult::Close;
+        }
+        state
+    }
+}
@@ -0,0 +1,72 @@
+use crate::{
+    app::waves::{Wave, WaveId},
+    hseparator,
+};
+
+use super::WindowResult;
+
+const TEST_CLOCK: &str = "test_clock";
+
+/// Choice of clock wave sampling wave in testbench
+pub struct DomainAssign {
+    pub wave: WaveId,
+    name: String,
+    clocks: Vec<(WaveId, String)>,
+    pub clock: Option<WaveId>,
+}
+
+impl DomainAssign {
+    pub fn new(waves: &[Wave], index: usize) -> Self {
+        let clocks: Vec<(WaveId, String)> = waves
+            .iter()
+            .filter(|w| w.is_clock())
+            .map(|w| (w.id(), w.name()))
+            .collect();
+        let clock = waves[index].domain();
+        Self {
+            wave: waves[index].id(),
+            name: waves[index].name(),
+            clock: clock.filter(|c| clocks.iter().any(|(id, _)| id == c)),
+            clocks,
+        }
+    }
+
+    fn clock_name(&self) -> &str {
+        self.clock
+            .and_then(|c| self.clocks.iter().find(|(id, _)| *id == c))
+            .map_or(TEST_CLOCK, |(_, name)| name.as_str())
+    }
+
+    pub fn display(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) -> WindowResult {
+        let mut state = WindowResult::Open;
+        let mut open = true;
+        egui::Window::new(format!("Clock domain of {}", self.name))
+            .open(&mut open)
+            .show(ctx, |ui| {
+                egui::ComboBox::new("wave_domain", "Clock")
+                    .selected_text(self.clock_name().to_string())
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.clock, None, TEST_CLOCK);
+                        for (id, name) in &self.clocks {
+                            ui.selectable_value(&mut self.clock, Some(*id), name);
+                        }
+                    });
+                if self.clocks.is_empty() {
+                    ui.label("Add wave of Clock type for other domains");
+                }
+                hseparator!(ui);
+                ui.horizontal(|ui| {
+                    if ui.button("Save").clicked() {
+                        state = WindowResult::Save;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        state = WindowResult::Cancel;
+                    }
+                });
+            });
+        if !open {
+            state = WindowResult::Close;
+        }
+        state
+    }
+}
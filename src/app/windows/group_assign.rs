@@ -0,0 +1,70 @@
+use crate::{
+    app::{
+        groups::{GroupId, Groups},
+        waves::{Wave, WaveId},
+    },
+    hseparator,
+};
+
+use super::WindowResult;
+
+/// Choice of group for wave, new group can be created inside of chosen one
+pub struct GroupAssign {
+    pub wave: WaveId,
+    name: String,
+    groups: Vec<(GroupId, String)>,
+    pub group: Option<GroupId>,
+    pub new_group: String,
+}
+
+impl GroupAssign {
+    pub fn new(waves: &[Wave], groups: &Groups, index: usize) -> Self {
+        Self {
+            wave: waves[index].id(),
+            name: waves[index].name(),
+            groups: groups.paths(waves),
+            group: waves[index].group().filter(|g| groups.get(*g).is_some()),
+            new_group: String::new(),
+        }
+    }
+
+    fn path(&self, group: Option<GroupId>) -> &str {
+        group
+            .and_then(|g| self.groups.iter().find(|(id, _)| *id == g))
+            .map_or("No group", |(_, path)| path.as_str())
+    }
+
+    pub fn display(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) -> WindowResult {
+        let mut state = WindowResult::Open;
+        let mut open = true;
+        egui::Window::new(format!("Group of {}", self.name))
+            .open(&mut open)
+            .show(ctx, |ui| {
+                egui::ComboBox::new("wave_group", "Group")
+                    .selected_text(self.path(self.group).to_string())
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.group, None, "No group");
+                        for (id, path) in &self.groups {
+                            ui.selectable_value(&mut self.group, Some(*id), path);
+                        }
+                    });
+                ui.horizontal(|ui| {
+                    ui.label("New group inside");
+                    ui.text_edit_singleline(&mut self.new_group);
+                });
+                hseparator!(ui);
+                ui.horizontal(|ui| {
+                    if ui.button("Save").clicked() {
+                        state = WindowResult::Save;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        state = WindowResult::Cancel;
+                    }
+                });
+            });
+        if !open {
+            state = WindowResult::Close;
+        }
+        state
+    }
+}
@@ -1,4 +1,7 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+};
 
 use anyhow::anyhow;
 use egui::Ui;
@@ -39,13 +42,16 @@ impl ToString for InputType {
 
 struct WaveWrapper {
     wave: Wave,
+    /// Names of VCD scopes from top
+    scope: Vec<String>,
     to_import: bool,
 }
 
-impl From<Wave> for WaveWrapper {
-    fn from(value: Wave) -> Self {
+impl From<(Wave, Vec<String>)> for WaveWrapper {
+    fn from((wave, scope): (Wave, Vec<String>)) -> Self {
         Self {
-            wave: value,
+            wave,
+            scope,
             to_import: false,
         }
     }
@@ -55,6 +61,7 @@ pub struct ImportData {
     input_tp_file: InputType,
     file_path: Option<PathBuf>,
     new_waves: Vec<WaveWrapper>,
+    keep_four_state: bool,
     unknown_value: bool,
     high_impedance: bool,
 }
@@ -65,6 +72,7 @@ impl Default for ImportData {
             input_tp_file: InputType::CSV,
             file_path: None,
             new_waves: Vec::new(),
+            keep_four_state: true,
             unknown_value: false,
             high_impedance: false,
         }
@@ -129,7 +137,9 @@ impl ImportData {
                             }
                         };
                     });
-                    self.display_new_values(ui);
+                    if self.display_new_values(ui) {
+                        state = WindowResult::Save;
+                    }
                 });
             });
         if !open {
@@ -140,6 +150,10 @@ impl ImportData {
 
     fn params_vcd(&mut self, ui: &mut Ui) {
         ui.vertical(|ui| {
+            ui.checkbox(&mut self.keep_four_state, "Keep X and Z states");
+            if self.keep_four_state {
+                return;
+            }
             ui.horizontal(|ui| {
                 ui.label("Unknow Value replace to:");
                 ui.checkbox(&mut self.unknown_value, "");
@@ -151,10 +165,28 @@ impl ImportData {
         });
     }
 
-    fn display_new_values(&mut self, ui: &mut Ui) {
-        if !self.new_waves.is_empty() && ui.button("Import all").clicked() {
-            self.new_waves.iter_mut().for_each(|v| {
-                v.to_import = true;
+    /// Waves chosen to import with their scopes
+    pub fn take_waves(&mut self) -> Vec<(Wave, Vec<String>)> {
+        std::mem::take(&mut self.new_waves)
+            .into_iter()
+            .filter(|w| w.to_import)
+            .map(|w| (w.wave, w.scope))
+            .collect()
+    }
+
+    fn display_new_values(&mut self, ui: &mut Ui) -> bool {
+        let mut save = false;
+        if !self.new_waves.is_empty() {
+            ui.horizontal(|ui| {
+                if ui.button("Import all").clicked() {
+                    self.new_waves.iter_mut().for_each(|v| {
+                        v.to_import = true;
+                    });
+                }
+                let chosen = self.new_waves.iter().any(|w| w.to_import);
+                save = ui
+                    .add_enabled(chosen, egui::Button::new("Import"))
+                    .clicked();
             });
         }
         let link_group_id = ui.id().with("link_waves");
@@ -170,6 +202,7 @@ impl ImportData {
                         link_group_id,
                         &egui::InputState::default(),
                         |_wave, ui| {
+                            ui.label(w.scope.join("."));
                             ui.checkbox(&mut w.to_import, "Import");
                         },
                     );
@@ -181,48 +214,51 @@ impl ImportData {
 
         // });
         // });
+        save
     }
 
-    fn import_vcd(&mut self, p: &PathBuf) -> Result<Vec<Wave>, anyhow::Error> {
+    /// Waves of VCD variables in order of declaration with their scopes
+    fn import_vcd(&mut self, p: &PathBuf) -> Result<Vec<(Wave, Vec<String>)>, anyhow::Error> {
         let f = std::fs::File::open(p)?;
         let mut parser = vcd::Parser::new(f);
         let header = parser.parse_header().map_err(|e| {
             warn!("VCD parse header: {:#}", e);
             anyhow!(e)
         })?;
-        let mut vars = HashMap::new();
+        let mut var_scopes = HashMap::new();
         let mut waves: HashMap<vcd::IdCode, Wave> = HashMap::new();
-        let mut stack = Vec::new();
-        stack.push((String::new(), header.items));
+        let mut order = Vec::new();
+        let mut stack: Vec<(Vec<String>, Vec<vcd::ScopeItem>)> = Vec::new();
+        stack.push((Vec::new(), header.items));
         while let Some((scope, items)) = stack.pop() {
+            let mut scopes = Vec::new();
             for i in items {
                 match i {
                     vcd::ScopeItem::Scope(s) => {
-                        stack.push((s.identifier, s.children));
+                        let mut path = scope.clone();
+                        path.push(s.identifier);
+                        scopes.push((path, s.children));
                     }
                     vcd::ScopeItem::Var(v) => {
                         let Some(nt) = vcd_type(&v) else {
                             continue;
                         };
-                        let mut w = Wave::new(
-                            format!("{}.{}", scope, v.reference.clone()),
-                            16,
-                            egui::Vec2::ZERO,
-                        );
+                        let mut w = Wave::new(v.reference.clone(), 16, egui::Vec2::ZERO);
                         w.set_type(nt);
+                        if let vcd::VarType::String = v.var_type {
+                            w.show_ascii();
+                        }
 
-                        waves.insert(v.code, w);
-                        vars.insert(
-                            v.code,
-                            VcdVar {
-                                scope: scope.clone(),
-                                var: v,
-                            },
-                        );
+                        if waves.insert(v.code, w).is_none() {
+                            order.push(v.code);
+                        }
+                        var_scopes.insert(v.code, scope.clone());
                     }
                     vcd::ScopeItem::Comment(_) => {}
                 }
             }
+            // First scope is taken first
+            stack.extend(scopes.into_iter().rev());
         }
         let mut _current_time = 0usize;
         let (time_div, s) = header.timescale.unwrap_or((1, vcd::TimescaleUnit::S));
@@ -248,7 +284,10 @@ impl ImportData {
                     _current_time = t as usize;
                 }
                 vcd::Command::ChangeScalar(id, v) => {
-                    warn!("Not implemented yet: {id}->{v}");
+                    if let Some(value) = waves.get(&id) {
+                        let b = self.vcd_value(value, &[v])?;
+                        waves.get_mut(&id).unwrap().set_last_value(b);
+                    }
                 }
                 vcd::Command::ChangeVector(id, v) => {
                     if v.len() > BitValue::BITS {
@@ -259,55 +298,77 @@ impl ImportData {
                         if value.export_type() == "wire" {
                             debug!("Wire detect");
                         }
-                        let mut b = BitValue::new(value.reg_size());
-                        let s = format!(
-                            "0b{}",
-                            v.iter()
-                                .map(|v| {
-                                    match v {
-                                        vcd::Value::V0 => '0',
-                                        vcd::Value::V1 => '1',
-                                        vcd::Value::X => {
-                                            if self.unknown_value {
-                                                '1'
-                                            } else {
-                                                '0'
-                                            }
-                                        }
-                                        vcd::Value::Z => {
-                                            if self.high_impedance {
-                                                '1'
-                                            } else {
-                                                '0'
-                                            }
-                                        }
-                                    }
-                                })
-                                .collect::<String>()
-                        );
-                        b.parse_from(&s).map_err(|v| {
-                            warn!("Error value: {}", s);
-                            anyhow!("Error change vector: {}", v)
-                        })?;
+                        let b = self.vcd_value(value, &v)?;
                         waves.get_mut(&id).unwrap().set_last_value(b);
                     }
                 }
                 vcd::Command::ChangeReal(id, v) => {
                     warn!("Not implemented Real value: {id}->{v:.2}");
                 }
-                vcd::Command::ChangeString(id, _) => {
-                    warn!("String unsupported: {id}");
+                vcd::Command::ChangeString(id, s) => {
+                    if let Some(w) = waves.get_mut(&id) {
+                        // String var has no size, register grows up to longest string
+                        let bits = s.len() * 8;
+                        if bits > w.reg_size() {
+                            if bits > BitValue::BITS {
+                                warn!("To big string to implement: {id}");
+                                continue;
+                            }
+                            w.set_type(WaveType::Reg(bits));
+                            w.show_ascii();
+                        }
+                        w.set_last_value(BitValue::from_bytes(w.reg_size(), s.as_bytes()));
+                    }
                 }
                 _ => warn!("Unknown vcd command"),
             }
         }
-        Ok(waves.into_values().collect())
+        let mut res: Vec<(Wave, Vec<String>)> = order
+            .into_iter()
+            .filter_map(|code| Some((waves.remove(&code)?, var_scopes.remove(&code)?)))
+            .collect();
+        let vars: Vec<(String, &[String])> =
+            res.iter().map(|(w, s)| (w.name(), s.as_slice())).collect();
+        let names = unique_names(&vars);
+        for ((w, _), name) in res.iter_mut().zip(names) {
+            w.set_name(name);
+        }
+        Ok(res)
     }
-}
 
-struct VcdVar {
-    scope: String,
-    var: vcd::Var,
+    fn vcd_value(&self, wave: &Wave, v: &[vcd::Value]) -> Result<BitValue, anyhow::Error> {
+        let mut b = BitValue::new(wave.reg_size());
+        let s = format!(
+            "0b{}",
+            v.iter()
+                .map(|v| match v {
+                    vcd::Value::V0 => '0',
+                    vcd::Value::V1 => '1',
+                    vcd::Value::X if self.keep_four_state => 'x',
+                    vcd::Value::X => {
+                        if self.unknown_value {
+                            '1'
+                        } else {
+                            '0'
+                        }
+                    }
+                    vcd::Value::Z if self.keep_four_state => 'z',
+                    vcd::Value::Z => {
+                        if self.high_impedance {
+                            '1'
+                        } else {
+                            '0'
+                        }
+                    }
+                })
+                .collect::<String>()
+        );
+        b.parse_from(&s).map_err(|v| {
+            warn!("Error value: {}", s);
+            anyhow!("Error change vector: {}", v)
+        })?;
+        Ok(b)
+    }
 }
 
 #[rustfmt::skip]
@@ -338,9 +399,55 @@ fn vcd_type(v: &vcd::Var)->Option<WaveType>{
             Some(WaveType::Reg(v.size as usize))
         },
         vcd::VarType::WOr     => {warn!("Unsupported type: WOr");None},
-        vcd::VarType::String =>  {warn!("Unsupported type: String");None}
+        vcd::VarType::String =>  {
+            Some(WaveType::Reg(8))
+        },
         t => {
             warn!("Unsupported type: {}", t.to_string()); None
         }
     }
 }
+
+/// Same names of different scopes get scope prefix, names still taken get
+/// numeric suffix
+fn unique_names(vars: &[(String, &[String])]) -> Vec<String> {
+    let mut used = HashSet::new();
+    vars.iter()
+        .map(|(name, scope)| {
+            let same = vars.iter().filter(|(n, _)| n == name).count();
+            let mut base = name.clone();
+            if same > 1 && !scope.is_empty() {
+                base = format!("{}_{}", scope.join("_"), name);
+            }
+            let mut unique = base.clone();
+            let mut n = 1;
+            while !used.insert(unique.clone()) {
+                unique = format!("{}_{}", base, n);
+                n += 1;
+            }
+            unique
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::unique_names;
+
+    #[test]
+    fn test_unique_names() {
+        let (top, a, b) = (vec![], vec!["a".to_string()], vec!["b".to_string()]);
+        let vars = [
+            ("clk".to_string(), top.as_slice()),
+            ("clk".to_string(), a.as_slice()),
+            ("a_b".to_string(), top.as_slice()),
+            ("b".to_string(), a.as_slice()),
+            ("b".to_string(), b.as_slice()),
+            ("b".to_string(), a.as_slice()),
+        ];
+        assert_eq!(
+            unique_names(&vars),
+            ["clk", "a_clk", "a_b", "a_b_1", "b_b", "a_b_2"]
+        );
+    }
+}
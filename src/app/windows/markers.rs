@@ -0,0 +1,83 @@
+use crate::{app::markers::Markers, hseparator};
+
+use super::{ProjectSettings, WindowResult};
+
+/// List of markers with jumps and cursors
+pub struct MarkerList {
+    settings: ProjectSettings,
+}
+
+impl MarkerList {
+    pub fn new(settings: ProjectSettings) -> Self {
+        Self { settings }
+    }
+
+    pub fn display(&mut self, ctx: &egui::Context, markers: &mut Markers) -> WindowResult {
+        let mut state = WindowResult::Open;
+        let mut open = true;
+        egui::Window::new("Markers")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let mut sort = false;
+                let mut remove = None;
+                let mut goto = None;
+                egui::Grid::new("marker_list").show(ui, |ui| {
+                    for (i, m) in markers.list.iter_mut().enumerate() {
+                        ui.text_edit_singleline(&mut m.name);
+                        let cycle =
+                            egui::DragValue::new(&mut m.cycle).clamp_range(0..=self.settings.max_time);
+                        let r = ui.add(cycle);
+                        sort |= r.drag_released() || r.lost_focus();
+                        if ui.button("Go to").clicked() {
+                            goto = Some(m.cycle);
+                        }
+                        if ui.button("🗑").clicked() {
+                            remove = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+                if let Some(i) = remove {
+                    markers.list.remove(i);
+                }
+                if sort {
+                    markers.sort();
+                }
+                if let Some(cycle) = goto {
+                    markers.jump(cycle);
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("Add").clicked() {
+                        markers.add(markers.cursors[0].unwrap_or(0));
+                    }
+                    if ui.button("⏴ Previous").clicked() {
+                        markers.prev();
+                    }
+                    if ui.button("Next ⏵").clicked() {
+                        markers.next();
+                    }
+                });
+                hseparator!(ui);
+                for (i, cursor) in markers.cursors.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        let mut shown = cursor.is_some();
+                        ui.checkbox(&mut shown, format!("Cursor {}", i + 1));
+                        let mut cycle = cursor.unwrap_or(0);
+                        ui.add_enabled(
+                            shown,
+                            egui::DragValue::new(&mut cycle).clamp_range(0..=self.settings.max_time),
+                        );
+                        *cursor = shown.then_some(cycle);
+                    });
+                }
+                if let Some(delta) = markers.delta_text(&self.settings.scale) {
+                    ui.label(delta);
+                }
+                ui.label("Over wave: M adds marker, 1 and 2 place cursors when edit cursor is hidden by Esc");
+            });
+        if !open {
+            state = WindowResult::Close;
+        }
+        state
+    }
+}
@@ -1,7 +1,15 @@
+mod bus_merge;
+mod domain_assign;
+mod group_assign;
+mod markers;
 mod project_export;
 mod project_settings;
 mod import;
 
+pub use bus_merge::BusMerge;
+pub use domain_assign::DomainAssign;
+pub use group_assign::GroupAssign;
+pub use markers::MarkerList;
 pub use project_export::ProjectExport;
 pub use project_settings::ProjectSettings;
 pub use import::ImportData;
@@ -4,7 +4,15 @@ use anyhow::{anyhow, Result};
 use handlebars::to_json;
 use log::{debug, error};
 
-use crate::{app::waves::Wave, hseparator};
+use crate::{
+    app::{
+        groups::{GroupId, Groups},
+        markers::{MarkerExport, Markers},
+        time::TimeScale,
+        waves::{clock_domains, Wave},
+    },
+    hseparator,
+};
 
 use super::WindowResult;
 
@@ -16,22 +24,70 @@ pub struct ProjectExport {
     generate_sv: bool,
     generate_mem: bool,
     export_folder: PathBuf,
+    markers: MarkerExport,
+    /// Only waves of group are exported
+    #[serde(skip)]
+    pub group: Option<(GroupId, String)>,
 }
 
-#[derive(serde::Serialize)]
+#[derive(Clone, serde::Serialize)]
 struct ExportVariable {
     tp: String,
+    /// Type of signal, differ from `tp` for enum
+    tp_signal: String,
+    /// Enum type to cast memory value
+    cast: Option<String>,
     name: String,
     name_data: String,
+    /// Index counter of clock domain
     index: String,
     name_file: String,
     memory_size: usize,
+    /// Path of group starting from this variable
+    group: Option<String>,
+}
+
+/// Variables assigned on rising edge of one clock
+#[derive(serde::Serialize)]
+struct ExportDomain {
+    clock: String,
+    index: String,
+    variables: Vec<ExportVariable>,
+}
+
+/// Clock generated from cycles of testbench clock
+#[derive(serde::Serialize)]
+struct ExportClock {
+    signal: String,
+    /// Complementary signal of differential pair
+    negative: Option<String>,
+    cycle: String,
+    level: String,
+}
+
+#[derive(serde::Serialize)]
+struct ExportMarker {
+    /// Escaped for string literal
+    name: String,
+    cycle: usize,
+    time: String,
+    /// Time from previous marker
+    delay: String,
 }
 
 #[derive(serde::Serialize)]
 struct ExportData {
+    typedefs: Vec<String>,
     variables: Vec<ExportVariable>,
-    end_time: usize,
+    domains: Vec<ExportDomain>,
+    clocks: Vec<ExportClock>,
+    timeunit: String,
+    timeprecision: String,
+    /// Delay between edges of clock
+    half_period: String,
+    end_time: String,
+    markers: Vec<ExportMarker>,
+    display_markers: bool,
 }
 
 impl ProjectExport {
@@ -39,9 +95,18 @@ impl ProjectExport {
         let mut state = WindowResult::Open;
         let mut open = true;
         egui::Window::new("Export").open(&mut open).show(ctx, |ui| {
+            if let Some((_, path)) = &self.group {
+                ui.label(format!("Group: {}", path));
+            }
             hseparator!(ui);
             ui.checkbox(&mut self.generate_sv, "Generate sv file");
             ui.checkbox(&mut self.generate_mem, "Generate mem file");
+            ui.horizontal(|ui| {
+                ui.label("Markers");
+                ui.selectable_value(&mut self.markers, MarkerExport::None, "No");
+                ui.selectable_value(&mut self.markers, MarkerExport::Comment, "Comments");
+                ui.selectable_value(&mut self.markers, MarkerExport::Display, "$display");
+            });
             ui.label(format!(
                 "File: {}",
                 self.export_folder.as_path().to_string_lossy()
@@ -64,16 +129,39 @@ impl ProjectExport {
         state
     }
 
-    pub fn generate_data(&mut self, waves: &Vec<Wave>) -> Result<()> {
-        self.generate_sv_file(waves)?;
-        self.generate_mem_files(waves)?;
+    /// Files for `waves` in given order, waves of one group go together
+    pub fn generate_data(
+        &mut self,
+        waves: &[&Wave],
+        groups: &Groups,
+        markers: &Markers,
+        scale: &TimeScale,
+    ) -> Result<()> {
+        // Clock of every wave, `None` is clock of testbench
+        let mut clocks = vec![None; waves.len()];
+        let domains = clock_domains(waves).map_err(|e| anyhow!(e))?;
+        for d in &domains {
+            for i in &d.waves {
+                clocks[*i] = d.clock.map(|c| waves[c]);
+            }
+        }
+        self.generate_sv_file(waves, &clocks, groups, markers, scale)?;
+        self.generate_mem_files(waves, &clocks)?;
 
         debug!("Generate files");
         Ok(())
     }
 
-    fn generate_sv_file(&mut self, waves: &Vec<Wave>) -> Result<()> {
+    fn generate_sv_file(
+        &mut self,
+        waves: &[&Wave],
+        clocks: &[Option<&Wave>],
+        groups: &Groups,
+        markers: &Markers,
+        scale: &TimeScale,
+    ) -> Result<()> {
         if self.generate_sv {
+            scale.check().map_err(|e| anyhow!(e))?;
             let Some(w) = waves.first() else {
                 error!("Nothing to generate add one signal");
                 return Err(anyhow!("Nothing to generate. Add at least one signal"));
@@ -83,19 +171,56 @@ impl ProjectExport {
             let fout = std::fs::File::create(self.export_folder.join("test.sv"))?;
 
             let mut data = ExportData {
-                end_time: w.len() * 2,
+                timeunit: scale.timeunit(),
+                timeprecision: scale.timeprecision(),
+                half_period: scale.delay(0.5),
+                end_time: scale.delay(w.len() as f64),
+                typedefs: Vec::new(),
                 variables: Vec::with_capacity(waves.len()),
+                domains: Vec::new(),
+                clocks: Vec::new(),
+                markers: Vec::new(),
+                display_markers: self.markers == MarkerExport::Display,
             };
+            if self.markers != MarkerExport::None {
+                data.markers = export_markers(markers, scale);
+            }
 
-            for wave in waves {
-                data.variables.push(ExportVariable {
+            let mut last_group = None;
+            for (wave, clock) in waves.iter().zip(clocks) {
+                if wave.is_clock() {
+                    data.clocks.push(export_clock(wave, waves)?);
+                    continue;
+                }
+                let typedef = wave.export_enum();
+                let group = wave.group().filter(|g| groups.get(*g).is_some());
+                let new_group = group != last_group;
+                last_group = group;
+                let (clock_name, index) = match clock {
+                    Some(c) => (c.clock_signal(), c.name() + "_index"),
+                    None => ("test_clock".into(), "test_index".into()),
+                };
+                let variable = ExportVariable {
                     tp: wave.export_type(),
+                    tp_signal: wave.export_signal_type(),
+                    cast: typedef.is_some().then(|| wave.export_signal_type()),
                     name: wave.name(),
                     name_data: wave.name() + "_data",
-                    index: wave.name() + "_index",
+                    index,
                     name_file: wave.name() + "_file.memb",
-                    memory_size: wave.len(),
-                })
+                    memory_size: clock.map_or(wave.len(), |c| c.rising_edges().len()),
+                    group: group.filter(|_| new_group).map(|g| groups.path(g)),
+                };
+                match data.domains.iter_mut().find(|d| d.clock == clock_name) {
+                    Some(d) => d.variables.push(variable.clone()),
+                    None => data.domains.push(ExportDomain {
+                        clock: clock_name,
+                        index: variable.index.clone(),
+                        variables: vec![variable.clone()],
+                    }),
+                }
+                data.variables.push(variable);
+                data.typedefs.extend(typedef);
             }
 
             hand.render_to_write(TEMPLATE_NAME, &to_json(data), fout)?;
@@ -103,23 +228,82 @@ impl ProjectExport {
         Ok(())
     }
 
-    fn generate_mem_files(&mut self, waves: &Vec<Wave>) -> Result<(), std::io::Error> {
+    fn generate_mem_files(
+        &mut self,
+        waves: &[&Wave],
+        clocks: &[Option<&Wave>],
+    ) -> Result<(), std::io::Error> {
         if self.generate_mem {
-            for wave in waves {
+            // Clocks are generated by testbench
+            let data = waves.iter().zip(clocks).filter(|(w, _)| !w.is_clock());
+            for (wave, clock) in data {
                 let path = self.export_folder.join(wave.name() + "_file.memb");
-                wave.generate_memb(path)?;
+                wave.generate_memb(path, *clock)?;
             }
         }
         Ok(())
     }
 }
 
+/// Clock counting cycles of testbench clock. Enable wave must be sampled by
+/// testbench clock, its memory is read by the same count
+fn export_clock(wave: &Wave, waves: &[&Wave]) -> Result<ExportClock> {
+    let cycle = wave.name() + "_cycle";
+    let gate = match wave.clock_gate() {
+        Some(g) => {
+            let Some(g) = waves.iter().find(|w| w.id() == g) else {
+                return Err(anyhow!("Enable of {} is not exported", wave.name()));
+            };
+            if g.is_clock() || g.domain().is_some() {
+                return Err(anyhow!(
+                    "Enable of {} must be sampled by test_clock",
+                    wave.name()
+                ));
+            }
+            let bit = if g.is_wire() { "" } else { "[0]" };
+            Some(format!("{}_data[{}]{}", g.name(), cycle, bit))
+        }
+        None => None,
+    };
+    Ok(ExportClock {
+        signal: wave.clock_signal(),
+        negative: wave.clock_negative(),
+        level: wave
+            .export_clock_level(&cycle, gate.as_deref())
+            .unwrap_or_default(),
+        cycle,
+    })
+}
+
+/// Markers with time of cycle start, samples are set on rising edge of clock
+/// in middle of first cycle
+fn export_markers(markers: &Markers, scale: &TimeScale) -> Vec<ExportMarker> {
+    let mut last = 0.0;
+    markers
+        .list
+        .iter()
+        .map(|m| {
+            let start = m.cycle as f64 + 0.5;
+            let delay = scale.delay(start - last);
+            last = start;
+            ExportMarker {
+                name: m.name.replace('\\', "\\\\").replace('"', "\\\""),
+                cycle: m.cycle,
+                time: scale.delay(start),
+                delay,
+            }
+        })
+        .collect()
+}
+
 impl Default for ProjectExport {
     fn default() -> Self {
         Self {
             generate_sv: true,
             generate_mem: true,
             export_folder: Path::new("./test").to_path_buf(),
+            markers: MarkerExport::None,
+            group: None,
         }
     }
 }
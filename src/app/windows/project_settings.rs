@@ -1,23 +1,44 @@
+use crate::app::time::{TimeScale, TimeUnit};
+
 use super::WindowResult;
 
 #[derive(Clone, Copy, serde::Deserialize, serde::Serialize)]
 #[serde(default)]
 pub struct ProjectSettings {
     pub max_time: usize,
+    pub scale: TimeScale,
 }
 
 impl ProjectSettings {
     pub fn display(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) -> WindowResult {
         let mut state = WindowResult::Open;
+        let checked = self.scale.check();
         egui::Window::new("Settings").show(ctx, |ui| {
             ui.vertical(|ui| {
                 ui.horizontal(|ui| {
                     ui.label("Time size");
-                    ui.add(egui::DragValue::new(&mut self.max_time));
-                })
+                    ui.add(egui::DragValue::new(&mut self.max_time).suffix(" cycles"));
+                });
+                ui.horizontal(|ui| {
+                    unit_combo(ui, "Time unit", &mut self.scale.unit);
+                    unit_combo(ui, "Precision", &mut self.scale.precision);
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Cycle");
+                    let step = egui::DragValue::new(&mut self.scale.step)
+                        .clamp_range(0.0..=f64::MAX)
+                        .suffix(self.scale.unit.name());
+                    ui.add(step);
+                });
+                if let Err(e) = &checked {
+                    ui.colored_label(egui::Color32::RED, e);
+                }
             });
             ui.horizontal(|ui| {
-                if ui.button("Save").clicked() {
+                if ui
+                    .add_enabled(checked.is_ok(), egui::Button::new("Save"))
+                    .clicked()
+                {
                     state = WindowResult::Save;
                 }
                 if ui.button("Cancel").clicked() {
@@ -29,8 +50,21 @@ impl ProjectSettings {
     }
 }
 
+fn unit_combo(ui: &mut egui::Ui, label: &str, unit: &mut TimeUnit) {
+    egui::ComboBox::new(label, label)
+        .selected_text(unit.name())
+        .show_ui(ui, |ui| {
+            for u in TimeUnit::ALL {
+                ui.selectable_value(unit, u, u.name());
+            }
+        });
+}
+
 impl Default for ProjectSettings {
     fn default() -> Self {
-        Self { max_time: 16 }
+        Self {
+            max_time: 16,
+            scale: TimeScale::default(),
+        }
     }
 }
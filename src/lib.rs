@@ -1,7 +1,9 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
 
-pub const PROJECT_FILE_NAME: &str = "project-0.1.ron";
+pub const PROJECT_FILE_NAME: &str = "project-0.2.ron";
+/// Names of project file in archives of older versions, still can be opened
+pub const OLD_PROJECT_FILE_NAMES: &[&str] = &["project-0.1.ron"];
 
 mod app;
 pub use app::App;